@@ -1,96 +1,117 @@
-use ansi_term::Colour::{Green, Black};
-use ansi_term::Style;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 
-use super::super::types::cell::Cell;
+use super::super::error::MazeError;
+use super::super::output;
+use super::super::types::cell::{BaseCell, Cell};
 use super::super::types::grid::Grid;
+use super::super::types::topology::Linkable;
 
-const EMPTY_CELL: &'static str = "   ";
-
-static ASCII_LOWER: [char; 62] = [
-'0', '1', '2', '3', '4', '5', '6', '7', '8', '9',
-'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j',
-'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't',
-'u', 'v', 'w', 'x', 'y', 'z', 'A', 'B', 'C', 'D',
-'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N',
-'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X',
-'Y', 'Z'
-];
-
-#[derive(Debug, Clone, Copy)]
-pub struct DistanceCell {
-    x: usize,
-    y: usize,
-    distance: Option<usize>,
-    is_path: bool,
-    // path: Vec<(usize, usize)>
-}
-
-impl Cell for DistanceCell {
-    fn new(x: usize, y: usize) -> DistanceCell {
-        DistanceCell {
-            x: x,
-            y: y,
-            distance: None,
-            is_path: false,
-            // path: Vec::new()
-        }
+/// Solver state of a cell, the payload of `DistanceCell`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Distance {
+    pub distance: Option<usize>,
+    pub is_path: bool
+}
+
+/// Cell of the grid returned by `calculate`.
+pub type DistanceCell = BaseCell<Distance>;
+
+impl BaseCell<Distance> {
+    pub fn distance(&self) -> Option<usize> {
+        self.payload.distance
     }
 
-    fn to_string(&self) -> String {
-        match self.distance {
-            Some(d) => {
-                if self.is_path {
-                    Style::new().on(Green).fg(Black).paint(format!(" {} ", ASCII_LOWER[d % 62])).to_string()
-                    // Green.paint(format!(" {} ", ASCII_LOWER[d % 62])).to_string()
-                } else {
-                    EMPTY_CELL.to_string()
-                }
-            },
-            _ => EMPTY_CELL.to_string()
+    /// Whether the cell lies on the path to the cell the grid was solved for.
+    pub fn is_path(&self) -> bool {
+        self.payload.is_path
+    }
+}
+
+impl Grid<DistanceCell> {
+    /// Prints the maze with the distances along the path marked.
+    pub fn print_solution(&self) {
+        print!("{}", output::ascii::format_distances(self));
+    }
+}
+
+/// Distances of every cell from a single root cell, either in steps or in
+/// the costs of the links passed, with the cell each was reached from.
+#[derive(Debug, Clone)]
+pub struct Distances {
+    root: usize,
+    distances: Vec<Option<usize>>,
+    previous: Vec<Option<usize>>
+}
+
+impl Distances {
+    /// Cell farthest from the root, and its distance.
+    pub fn farthest(&self) -> (usize, usize) {
+        let mut res = (self.root, 0);
+        for (id, distance) in self.distances.iter().enumerate() {
+            match *distance {
+                Some(d) if d > res.1 => res = (id, d),
+                _ => {}
+            }
         }
+
+        res
     }
 
-    fn x(&self) -> usize {
-        self.x
+    /// Distance of the cell from the root, `None` if it is unreachable.
+    pub fn get(&self, id: usize) -> Option<usize> {
+        self.distances[id]
     }
 
-    fn y(&self) -> usize {
-        self.y
+    /// Cells on a shortest (or, for weighted distances, cheapest) path from
+    /// the root to `goal`, root first. Empty if `goal` is unreachable.
+    ///
+    /// The path is followed back the way the search went, so it also passes
+    /// one-way passages and links between cells which aren't adjacent.
+    pub fn path_to(&self, goal: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        if self.distances[goal].is_none() {
+            return path;
+        }
+
+        let mut current = Some(goal);
+        while let Some(id) = current {
+            path.push(id);
+            current = self.previous[id];
+        }
+
+        path.reverse();
+        path
     }
-}
 
-impl DistanceCell {
-    pub fn distance(&self) -> Option<usize> {
-        self.distance
+    pub fn root(&self) -> usize {
+        self.root
     }
 }
 
-pub fn calculate<T>(grid: &Grid<T>, begin: (usize, usize), end: (usize, usize)) -> Grid<DistanceCell>
-    where T: Cell + Clone + Copy
+/// Breadth-first search from `root` over the links of any topology.
+pub fn distances<M>(maze: &M, root: usize) -> Distances
+    where M: Linkable
 {
-    let mut distance_grid: Grid<DistanceCell> = Grid::new(grid.x(), grid.y());
-
-    distance_grid.links = grid.links.clone();
+    let mut distances = vec![None; maze.size()];
+    let mut previous = vec![None; maze.size()];
 
     let mut frontier = Vec::new();
-    distance_grid[begin.0][begin.1].distance = Some(0);
-    frontier.push(distance_grid[begin.0][begin.1]);
+    distances[root] = Some(0);
+    frontier.push(root);
 
+    let mut distance = 0;
     while frontier.len() > 0 {
         // Crete new frontiers
         let mut new_frontier = Vec::new();
+        distance += 1;
 
         for f in frontier {
-            let f_distance = f.distance.unwrap();
-
-            // Traverse linked neighbors and find closest one
-            for neighbor in grid.neighbors_linked_indices(f.x(), f.y()) {
-                match distance_grid[neighbor.x()][neighbor.y()].distance() {
-                    Some(_d) => {},
-                    _ => {
-                        distance_grid[neighbor.x()][neighbor.y()].distance = Some(f_distance + 1);
-                        new_frontier.push(distance_grid[neighbor.x()][neighbor.y()]);
-                    }
+            for neighbor in maze.linked_ids(f) {
+                if distances[neighbor].is_none() {
+                    distances[neighbor] = Some(distance);
+                    previous[neighbor] = Some(f);
+                    new_frontier.push(neighbor);
                 }
             }
         }
@@ -99,40 +120,200 @@ pub fn calculate<T>(grid: &Grid<T>, begin: (usize, usize), end: (usize, usize))
         frontier = new_frontier;
     }
 
-    // TODO: Store Path Incrementaly Here
-    let _path: Vec<(usize, usize)> = Vec::new();
+    Distances {
+        root: root,
+        distances: distances,
+        previous: previous
+    }
+}
 
-    let mut current = Some(distance_grid[end.0][end.1]);
-    distance_grid[end.0][end.1].is_path = true;
+/// Entry of the priority queue, ordered so that `BinaryHeap` pops the
+/// lowest distance first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct State {
+    distance: usize,
+    id: usize
+}
 
-    while current.is_some() {
-        let c = current.unwrap();
-        let mut new = None;
-        for n in distance_grid.neighbors_linked_indices(c.x(), c.y()) {
-            if n.distance.unwrap() < c.distance.unwrap() {
-                new = Some(n);
-            }
+impl Ord for State {
+    fn cmp(&self, other: &State) -> Ordering {
+        (other.distance, other.id).cmp(&(self.distance, self.id))
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &State) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Dijkstra's algorithm from `root`, adding up `Linkable::cost_ids` of the
+/// links passed instead of counting steps.
+pub fn weighted_distances<M>(maze: &M, root: usize) -> Distances
+    where M: Linkable
+{
+    let mut distances = vec![None; maze.size()];
+    let mut previous = vec![None; maze.size()];
+    let mut queue = BinaryHeap::new();
+
+    distances[root] = Some(0);
+    queue.push(State { distance: 0, id: root });
+
+    while let Some(State { distance, id }) = queue.pop() {
+        // Stale entry, the cell has been reached more cheaply since
+        if distances[id].map_or(false, |d| distance > d) {
+            continue;
         }
 
-        if new.is_some() {
-            let n = new.unwrap();
-            distance_grid[n.x()][n.y()].is_path = true;
+        for neighbor in maze.linked_ids(id) {
+            let next = distance + maze.cost_ids(id, neighbor);
+            match distances[neighbor] {
+                Some(d) if d <= next => {},
+                _ => {
+                    distances[neighbor] = Some(next);
+                    previous[neighbor] = Some(id);
+                    queue.push(State { distance: next, id: neighbor });
+                }
+            }
         }
+    }
 
-        current = new;
+    Distances {
+        root: root,
+        distances: distances,
+        previous: previous
     }
+}
 
+/// Steps from `begin` to every cell, with the shortest path to `end` marked.
+pub fn calculate<T>(grid: &Grid<T>, begin: (usize, usize), end: (usize, usize)) -> Grid<DistanceCell>
+    where T: Cell + Clone
+{
+    let distances = distances(grid, grid.shape().id(begin.0, begin.1));
+    distance_grid(grid, &distances, end)
+}
+
+/// Costs from `begin` to every cell, as set by `Grid::set_weight` and
+/// `Grid::set_link_weight`, with the cheapest path to `end` marked. Where
+/// the maze has loops it may be longer than the shortest path.
+pub fn calculate_weighted<T>(grid: &Grid<T>, begin: (usize, usize), end: (usize, usize)) -> Grid<DistanceCell>
+    where T: Cell + Clone
+{
+    let distances = weighted_distances(grid, grid.shape().id(begin.0, begin.1));
+    distance_grid(grid, &distances, end)
+}
+
+/// Same as `calculate`, failing when either cell is outside of the grid or
+/// `end` can't be reached from `begin`.
+pub fn try_calculate<T>(grid: &Grid<T>, begin: (usize, usize), end: (usize, usize)) -> Result<Grid<DistanceCell>, MazeError>
+    where T: Cell + Clone
+{
+    try_solve(grid, begin, end, calculate)
+}
+
+/// Same as `calculate_weighted`, failing like `try_calculate`.
+pub fn try_calculate_weighted<T>(grid: &Grid<T>, begin: (usize, usize), end: (usize, usize)) -> Result<Grid<DistanceCell>, MazeError>
+    where T: Cell + Clone
+{
+    try_solve(grid, begin, end, calculate_weighted)
+}
+
+fn try_solve<T, F>(grid: &Grid<T>, begin: (usize, usize), end: (usize, usize), solve: F) -> Result<Grid<DistanceCell>, MazeError>
+    where T: Cell + Clone,
+          F: Fn(&Grid<T>, (usize, usize), (usize, usize)) -> Grid<DistanceCell>
+{
+    if let Err(e) = grid.check_indices(begin.0, begin.1).and(grid.check_indices(end.0, end.1)) {
+        return Err(e);
+    }
+
+    let distances = solve(grid, begin, end);
+    match distances[end.0][end.1].distance() {
+        Some(_) => Ok(distances),
+        None => Err(MazeError::Unreachable(end.0, end.1))
+    }
+}
+
+fn distance_grid<T>(grid: &Grid<T>, distances: &Distances, end: (usize, usize)) -> Grid<DistanceCell>
+    where T: Cell + Clone
+{
+    let mut distance_grid: Grid<DistanceCell> = Grid::with_wrap(grid.x(), grid.y(), grid.shape().wrap());
+
+    distance_grid.links = grid.links.clone();
+    distance_grid.set_entrance(grid.entrance());
+    distance_grid.set_exit(grid.exit());
+
+    let shape = *grid.shape();
+
+    for id in 0..grid.size() {
+        let (x, y) = shape.coords(id);
+        distance_grid[x][y].payload.distance = distances.get(id);
+    }
+
+    for id in distances.path_to(shape.id(end.0, end.1)) {
+        let (x, y) = shape.coords(id);
+        distance_grid[x][y].payload.is_path = true;
+    }
 
     return distance_grid;
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::super::error::MazeError;
     use super::super::super::types::cell::*;
     use super::super::super::distance;
     use super::super::super::types::grid::Grid;
     use test::Bencher;
 
+    #[test]
+    fn test_fastest_path_avoids_heavy_cells() {
+        // Open 3x3 room with a swamp in the middle
+        let mut grid: Grid<BaseCell> = Grid::new(3, 3);
+        for x in 0..3 {
+            for y in 0..3 {
+                if x + 1 < 3 {
+                    grid.link_indices(x, y, x + 1, y);
+                }
+                if y + 1 < 3 {
+                    grid.link_indices(x, y, x, y + 1);
+                }
+            }
+        }
+        grid.set_weight(1, 1, 10);
+        grid.set_weight(1, 0, 10);
+
+        let shortest = distance::dijkstra::distances(&grid, 0);
+        let fastest = distance::dijkstra::weighted_distances(&grid, 0);
+        assert_eq!(shortest.get(grid.shape().id(2, 0)), Some(2));
+        assert_eq!(fastest.get(grid.shape().id(2, 0)), Some(6));
+        assert_eq!(fastest.path_to(grid.shape().id(2, 0)).len(), 7);
+    }
+
+    #[test]
+    fn test_one_way_passages() {
+        // Corridor of three cells, the second passage one-way eastwards
+        let mut grid: Grid<BaseCell> = Grid::new(3, 1);
+        grid.link_indices(0, 0, 1, 0);
+        grid.link_one_way_indices(1, 0, 2, 0);
+        assert!(grid.is_open_indices(2, 0, 1, 0));
+
+        let forward = distance::dijkstra::distances(&grid, 0);
+        assert_eq!(forward.path_to(2), vec![0, 1, 2]);
+
+        let backward = distance::dijkstra::distances(&grid, 2);
+        assert_eq!(backward.get(0), None);
+    }
+
+    #[test]
+    fn test_try_calculate() {
+        let mut grid: Grid<BaseCell> = Grid::new(3, 1);
+        grid.link_indices(0, 0, 1, 0);
+
+        assert!(distance::dijkstra::try_calculate(&grid, (0, 0), (1, 0)).is_ok());
+        assert_eq!(distance::dijkstra::try_calculate(&grid, (0, 0), (2, 0)).err(), Some(MazeError::Unreachable(2, 0)));
+        assert_eq!(distance::dijkstra::try_calculate(&grid, (0, 0), (3, 0)).err(), Some(MazeError::OutOfBounds(3, 0)));
+    }
+
     #[bench]
     fn bench_calculate_10x10(b: &mut Bencher) {
         b.iter(|| {
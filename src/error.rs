@@ -0,0 +1,54 @@
+use std::error::Error;
+use std::fmt;
+
+/// Errors of the fallible (`try_`) variants of the API, for callers which
+/// can't afford a panic, such as a server handling requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MazeError {
+    /// Grid without any cells, `(x, y)` being its size.
+    EmptyGrid(usize, usize),
+    /// Maze of any other shape without any cells.
+    EmptyMaze,
+    /// Cell outside of the grid.
+    OutOfBounds(usize, usize),
+    /// Cell which can't be reached from the start.
+    Unreachable(usize, usize),
+    /// Weight of a cell or a link below 1.
+    ZeroWeight,
+    /// Document which doesn't describe a valid maze, with the reason.
+    Input(String),
+    /// Output which couldn't be written, with the reason.
+    Output(String),
+    /// Grids which can't be transformed or joined, with the reason.
+    Transform(String)
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MazeError::EmptyGrid(x, y) => write!(f, "grid of {}x{} cells is empty", x, y),
+            MazeError::EmptyMaze => write!(f, "maze has no cells"),
+            MazeError::OutOfBounds(x, y) => write!(f, "cell ({}, {}) is outside of the grid", x, y),
+            MazeError::Unreachable(x, y) => write!(f, "cell ({}, {}) can't be reached", x, y),
+            MazeError::ZeroWeight => write!(f, "weight has to be at least 1"),
+            MazeError::Input(ref reason) => write!(f, "invalid maze: {}", reason),
+            MazeError::Output(ref reason) => write!(f, "output failed: {}", reason),
+            MazeError::Transform(ref reason) => write!(f, "transformation failed: {}", reason)
+        }
+    }
+}
+
+impl Error for MazeError {
+    fn description(&self) -> &str {
+        match *self {
+            MazeError::EmptyGrid(_, _) => "empty grid",
+            MazeError::EmptyMaze => "empty maze",
+            MazeError::OutOfBounds(_, _) => "cell out of bounds",
+            MazeError::Unreachable(_, _) => "unreachable cell",
+            MazeError::ZeroWeight => "zero weight",
+            MazeError::Input(_) => "invalid maze",
+            MazeError::Output(_) => "output failed",
+            MazeError::Transform(_) => "transformation failed"
+        }
+    }
+}
@@ -1,34 +1,85 @@
 extern crate rand;
 
 use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
 
-use super::super::types::cell::Cell;
-use super::super::types::grid::Grid;
+use super::super::types::topology::Linkable;
 
-pub fn generate<T>(grid: &mut Grid<T>)
-    where T: Cell + Clone
+pub fn generate<M>(maze: &mut M)
+    where M: Linkable
 {
-    let mut cell = grid.random_cell();
-    let mut unvisited = grid.size() - 1;
+    generate_with_rng(maze, &mut rand::thread_rng())
+}
+
+/// Same as `generate`, drawing every random choice from `rng`, so a seeded
+/// generator always carves the same maze.
+///
+/// Every region of the maze is carved on its own, the walk can't leave it.
+pub fn generate_with_rng<M, R>(maze: &mut M, rng: &mut R)
+    where M: Linkable, R: Rng
+{
+    for region in maze.regions() {
+        let mut cell = region[Range::new(0, region.len()).ind_sample(rng)];
+        let mut unvisited = region.len() - 1;
 
-    while unvisited > 0 {
-        let neighbors = grid.neighbors(&cell);
-        let neighbor = rand::thread_rng().choose(&neighbors).unwrap();
+        while unvisited > 0 {
+            let neighbors = maze.neighbor_ids(cell);
+            let neighbor = *rng.choose(&neighbors).unwrap();
 
-        if !grid.links.contains_key(&(neighbor.x(), neighbor.y())) {
-            grid.link(&cell, &neighbor);
-            unvisited -= 1;
-        }
+            if !maze.has_links_id(neighbor) {
+                maze.link_ids(cell, neighbor);
+                unvisited -= 1;
+            }
 
-        cell = neighbor.clone();
+            cell = neighbor;
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+    use std::collections::HashMap;
+    use std::mem;
+
     use super::super::super::types::cell::*;
     use super::super::super::types::grid::Grid;
-    use test::Bencher;
+    use test::{black_box, Bencher};
+
+    /// Links of the grid as they were stored before `Links`, a set of linked
+    /// cells per cell.
+    fn hashmap_links(grid: &Grid<BaseCell>) -> HashMap<(usize, usize), BTreeSet<(usize, usize)>> {
+        let mut links = HashMap::new();
+        for (from, to) in grid.links.pairs() {
+            links.entry(from).or_insert_with(BTreeSet::new).insert(to);
+        }
+
+        links
+    }
+
+    /// Bytes of a 100x100 maze in `Links`, against a lower bound for the
+    /// map it replaced: its entries and the cells in the sets, leaving out
+    /// the nodes of the sets and the bookkeeping of the map.
+    /// `cargo test links_memory -- --nocapture` prints both.
+    #[test]
+    fn test_links_memory_100x100() {
+        let mut grid: Grid<BaseCell> = Grid::new(100, 100);
+        super::generate(&mut grid);
+        let cells = grid.size();
+
+        let links = grid.links.heap_size();
+        let map = hashmap_links(&grid);
+        let entry = mem::size_of::<((usize, usize), BTreeSet<(usize, usize)>)>();
+        let set: usize = map.values().map(|set| set.len() * mem::size_of::<(usize, usize)>()).sum();
+        let map_size = map.capacity() * entry + set;
+
+        println!("Links: {} bytes, {:.2} per cell", links, links as f64 / cells as f64);
+        println!("HashMap: over {} bytes, {:.1} per cell", map_size, map_size as f64 / cells as f64);
+
+        // Two bits per cell, rounded up to whole words
+        assert_eq!(links, (cells * 2 + 63) / 64 * 8);
+        assert!(map_size > 100 * links);
+    }
 
     #[bench]
     fn bench_generate_10x10(b: &mut Bencher) {
@@ -45,4 +96,50 @@ mod tests {
             super::generate(&mut grid);
         });
     }
-}
\ No newline at end of file
+
+    #[bench]
+    fn bench_is_linked_100x100(b: &mut Bencher) {
+        let mut grid: Grid<BaseCell> = Grid::new(100, 100);
+        super::generate(&mut grid);
+
+        b.iter(|| {
+            let mut count = 0;
+            for x in 0..99 {
+                for y in 0..99 {
+                    if grid.is_linked_indices(x, y, x + 1, y) {
+                        count += 1;
+                    }
+                    if grid.is_linked_indices(x, y, x, y + 1) {
+                        count += 1;
+                    }
+                }
+            }
+            black_box(count)
+        });
+    }
+
+    /// Same as `bench_is_linked_100x100`, with the links kept in a map of
+    /// sets as `Grid` used to keep them, for comparison.
+    #[bench]
+    fn bench_is_linked_hashmap_100x100(b: &mut Bencher) {
+        let mut grid: Grid<BaseCell> = Grid::new(100, 100);
+        super::generate(&mut grid);
+
+        let links = hashmap_links(&grid);
+
+        b.iter(|| {
+            let mut count = 0;
+            for x in 0..99 {
+                for y in 0..99 {
+                    if links.get(&(x, y)).map_or(false, |set| set.contains(&(x + 1, y))) {
+                        count += 1;
+                    }
+                    if links.get(&(x, y)).map_or(false, |set| set.contains(&(x, y + 1))) {
+                        count += 1;
+                    }
+                }
+            }
+            black_box(count)
+        });
+    }
+}
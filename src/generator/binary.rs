@@ -4,23 +4,24 @@ use rand::Rng;
 
 use super::super::types::cell::Cell;
 use super::super::types::grid::Grid;
+use super::super::types::rectangle::Direction;
 
 pub fn generate<T>(grid: &mut Grid<T>)
     where T: Cell + Clone
 {
     grid.visit(|grid, cell| {
-        let mut cells: Vec<T> = Vec::new();
-
-        if cell.x() < (grid.x() - 1) {
-            cells.push(grid[cell.x() + 1][cell.y()].clone());
+        if grid.is_masked(cell.x(), cell.y()) {
+            return;
         }
 
-        if cell.y() < (grid.y() - 1) {
-            cells.push(grid[cell.x()][cell.y() + 1].clone());
-        }
+        // Passages across joined edges of wrapped grids would close loops
+        let cells: Vec<(usize, usize)> = [Direction::East, Direction::South].iter()
+            .filter_map(|&direction| grid.neighbor((cell.x(), cell.y()), direction))
+            .filter(|&(x, y)| x >= cell.x() && y >= cell.y())
+            .collect();
 
-        if cells.len() > 0 {
-            grid.link(cell, rand::thread_rng().choose(&cells).unwrap());
+        if let Some(&(x, y)) = rand::thread_rng().choose(&cells) {
+            grid.link_indices(cell.x(), cell.y(), x, y);
         }
     });
 }
@@ -4,6 +4,7 @@ use rand::Rng;
 
 use super::super::types::cell::Cell;
 use super::super::types::grid::Grid;
+use super::super::types::rectangle::Direction;
 
 pub fn generate<T>(grid: &mut Grid<T>)
     where T: Cell + Clone
@@ -11,9 +12,14 @@ pub fn generate<T>(grid: &mut Grid<T>)
     for y in 0..grid.y() {
         let mut cells: Vec<T> = Vec::new();
         for x in 0..grid.x() {
+            if grid.is_masked(x, y) {
+                continue;
+            }
+
             cells.push(grid.cells[x][y].clone());
 
-            let at_eastern_boundary = x == grid.x() - 1;
+            // A masked cell ends the run as the boundary does
+            let at_eastern_boundary = x == grid.x() - 1 || grid.is_masked(x + 1, y);
             let at_northern_boundary = y == grid.y() - 1;
 
             let should_close_out = at_eastern_boundary || (!at_northern_boundary && rand::thread_rng().gen());
@@ -21,12 +27,14 @@ pub fn generate<T>(grid: &mut Grid<T>)
             let mut should_clear = false;
             if should_close_out {
                 let member = rand::thread_rng().choose(&cells).unwrap();
-                if y < (grid.y() - 1) {
-                    grid.link_indices(member.x(), member.y(), member.x(), member.y() + 1);
+                if !at_northern_boundary {
+                    if let Some((sx, sy)) = grid.neighbor((member.x(), member.y()), Direction::South) {
+                        grid.link_indices(member.x(), member.y(), sx, sy);
+                    }
                 }
                 should_clear = true;
-            } else {
-                grid.link_indices(x, y, x + 1, y);
+            } else if let Some((ex, ey)) = grid.neighbor((x, y), Direction::East) {
+                grid.link_indices(x, y, ex, ey);
             }
 
             if should_clear {
@@ -1,65 +1,94 @@
 extern crate rand;
 
 use rand::Rng;
-// use std::collections::BTreeSet;
 
-use super::super::types::cell::Cell;
-use super::super::types::grid::Grid;
+use super::super::types::topology::Linkable;
 
-pub fn generate<T>(grid: &mut Grid<T>)
-    where T: Cell + Clone
+pub fn generate<M>(maze: &mut M)
+    where M: Linkable
 {
-    let mut unvisited = Vec::new();
-    for x in 0..grid.x() {
-        for y in 0..grid.y() {
-            unvisited.push((x, y));
-        }
-    }
+    let mut unvisited: Vec<usize> = (0..maze.size()).collect();
 
-    let first = rand::thread_rng().choose(&unvisited).unwrap().clone();
-    unvisited.retain(|&x| x != first);
-    println!("Starting cell: {:?}", first);
+    // Every region needs a cell of the maze for its walks to end in
+    for region in maze.regions() {
+        let first = rand::thread_rng().choose(&region).unwrap().clone();
+        unvisited.retain(|&x| x != first);
+        debug!("Starting cell: {:?}", first);
+    }
 
     while unvisited.len() > 0 {
-        let mut cell: (usize, usize) = rand::thread_rng().choose(&unvisited).unwrap().clone();
-        let mut path: Vec<(usize, usize)> = Vec::new();
+        let mut cell = rand::thread_rng().choose(&unvisited).unwrap().clone();
+        let mut path: Vec<usize> = Vec::new();
 
-        println!("Adding {:?}", cell);
+        debug!("Adding {:?}", cell);
         path.push(cell);
 
         while unvisited.contains(&cell) {
-            let neighbors = grid.neighbors_indices(cell.0, cell.1);
+            let neighbors = maze.neighbor_ids(cell);
 
-            let r = rand::thread_rng().choose(&neighbors).unwrap();
-            cell = (r.x(), r.y());
+            cell = *rand::thread_rng().choose(&neighbors).unwrap();
 
-            println!("Looking for: {:?}", cell);
-            match path.binary_search(&cell) {
-                Ok(position) => {
-                    path = path[0..position].to_vec();
-                    println!("Found, position: {:?}, new path: {:?}", position, path);
+            debug!("Looking for: {:?}", cell);
+            match path.iter().position(|&visited| visited == cell) {
+                Some(position) => {
+                    // Erase the loop, keeping the cell it started from
+                    path.truncate(position + 1);
+                    debug!("Found, position: {:?}, new path: {:?}", position, path);
                 }
-                _ => {
+                None => {
                     path.push(cell);
-                    println!("Not found, new path {:?}", path);
+                    debug!("Not found, new path {:?}", path);
                 }
             }
         }
 
-        println!("Unvisited does not contains: {:?}", cell);
-        println!("Path: {:?}", path);
+        debug!("Unvisited does not contains: {:?}", cell);
+        debug!("Path: {:?}", path);
 
-        // return;
-
-        // println!("{:?}", path.len());
-        let len = path.len() - 2;
-        for i in 0..len {
+        // The last cell of the path is already part of the maze
+        for i in 0..path.len() - 1 {
             let a = path[i];
             let b = path[i + 1];
-            grid.link_indices(a.0, a.1, b.0, b.1);
+            maze.link_ids(a, b);
             unvisited.retain(|&x| x != a);
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::distance;
+    use super::super::super::types::cell::*;
+    use super::super::super::types::grid::Grid;
+    use super::super::super::types::hex::Hex;
+    use super::super::super::types::maze::Maze;
+    use super::super::super::types::topology::Linkable;
+
+    /// Whether the maze is a spanning tree, every cell reachable and no
+    /// more passages than needed for that.
+    fn is_spanning_tree<M>(maze: &M) -> bool
+        where M: Linkable
+    {
+        let distances = distance::dijkstra::distances(maze, 0);
+        let links: usize = (0..maze.size()).map(|id| maze.linked_ids(id).len()).sum();
+        (0..maze.size()).all(|id| distances.get(id).is_some()) && links == 2 * (maze.size() - 1)
+    }
+
+    #[test]
+    fn test_generate_spanning_tree() {
+        for _ in 0..10 {
+            let mut grid: Grid<BaseCell> = Grid::new(8, 6);
+            super::generate(&mut grid);
+            assert!(is_spanning_tree(&grid));
+
+            let mut maze = Maze::new(Hex::new(5, 4));
+            super::generate(&mut maze);
+            assert!(is_spanning_tree(&maze));
+        }
 
-        grid.print_ascii();
+        // A single step from the first cell
+        let mut grid: Grid<BaseCell> = Grid::new(2, 1);
+        super::generate(&mut grid);
+        assert!(grid.is_linked_indices(0, 0, 1, 0));
     }
-}
\ No newline at end of file
+}
@@ -22,8 +22,12 @@ extern crate time;
 extern crate urlencoded;
 
 pub mod distance;
+pub mod error;
 pub mod generator;
 pub mod output;
+pub mod placement;
+pub mod transform;
 pub mod types;
+pub mod validation;
 pub mod web;
 
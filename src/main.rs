@@ -5,18 +5,36 @@ extern crate env_logger;
 extern crate clap;
 extern crate css_color_parser;
 extern crate maze;
+extern crate rand;
 extern crate serde_json;
 extern crate time;
 
-use clap::{App, Arg};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use rand::Rng;
 use css_color_parser::Color as CssColor;
 
 use maze::distance;
+use maze::error::MazeError;
+use maze::generator;
 use maze::types::cell::BaseCell;
+use maze::types::cube::Cube;
+use maze::types::delta::Delta;
+use maze::types::graph::Graph;
 use maze::types::grid::Grid;
+use maze::types::hex::Hex;
+use maze::types::levels::Levels;
+use maze::types::maze::Maze;
+use maze::types::polar::Polar;
+use maze::types::rectangle::Wrap;
+use maze::types::topology::{Linkable, Topology};
+use maze::types::upsilon::Upsilon;
+use maze::types::voronoi::Voronoi;
+use maze::types::world::World;
 use maze::web;
 
 use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
 use std::process::exit;
 use std::str::FromStr;
 
@@ -25,8 +43,10 @@ const DESCRIPTION: &'static str = "Maze Generator";
 const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
 const DEFAULT_CELL_SIZE: u32 = 80;
+const DEFAULT_CHUNK_SIZE: usize = 16;
 const DEFAULT_WALL_SIZE: u32 = 20;
 const DEFAULT_HEIGHT: usize = 5;
+const DEFAULT_LEVELS: usize = 1;
 const DEFAULT_WIDTH: usize = 5;
 const DEFAULT_PORT: u16 = 5000;
 const DEFAULT_COLOR_CELL: [u8; 3] = [255, 255, 255];
@@ -53,10 +73,85 @@ impl FromStr for Algorithm {
     }
 }
 
+enum GridKind {
+    Cube,
+    Delta,
+    Graph,
+    Hex,
+    Polar,
+    Rect,
+    Upsilon,
+    Voronoi,
+    World
+}
+
+impl FromStr for GridKind {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cube" => Ok(GridKind::Cube),
+            "delta" => Ok(GridKind::Delta),
+            "graph" => Ok(GridKind::Graph),
+            "hex" => Ok(GridKind::Hex),
+            "polar" => Ok(GridKind::Polar),
+            "rect" => Ok(GridKind::Rect),
+            "upsilon" => Ok(GridKind::Upsilon),
+            "voronoi" => Ok(GridKind::Voronoi),
+            "world" => Ok(GridKind::World),
+            _ => Err("no match")
+        }
+    }
+}
+
+enum Placement {
+    Corners,
+    LongestPath,
+    Random
+}
+
+impl FromStr for Placement {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "corners" => Ok(Placement::Corners),
+            "longest-path" => Ok(Placement::LongestPath),
+            "random" => Ok(Placement::Random),
+            _ => Err("no match")
+        }
+    }
+}
+
+enum WrapKind {
+    Cylinder,
+    Klein,
+    Mobius,
+    None,
+    Torus
+}
+
+impl FromStr for WrapKind {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cylinder" => Ok(WrapKind::Cylinder),
+            "klein" => Ok(WrapKind::Klein),
+            "mobius" => Ok(WrapKind::Mobius),
+            "none" => Ok(WrapKind::None),
+            "torus" => Ok(WrapKind::Torus),
+            _ => Err("no match")
+        }
+    }
+}
+
+#[derive(PartialEq)]
 enum Format {
     Ascii,
     Json,
-    Png
+    Png,
+    Svg
 }
 
 impl FromStr for Format {
@@ -67,15 +162,290 @@ impl FromStr for Format {
             "ascii" => Ok(Format::Ascii),
             "json" => Ok(Format::Json),
             "png" => Ok(Format::Png),
+            "svg" => Ok(Format::Svg),
             _ => Err("no match")
         }
     }
 }
 
+/// Carves any topology using one of the topology independent algorithms,
+/// binary and sidewinder being rejected by `check_algorithm`.
+fn generate<M>(maze: &mut M, algorithm: &Result<Algorithm, &'static str>)
+    where M: Linkable
+{
+    match *algorithm {
+        Ok(Algorithm::AldousBroder) => {
+            info!("Generating maze using Aldous-Broder algorithm");
+            generator::aldous_broder::generate(maze)
+        },
+        Ok(Algorithm::Wilson) => {
+            info!("Generating maze using Wilson's algorithm");
+            generator::wilson::generate(maze)
+        },
+        // Rejected along with the other arguments
+        Ok(_) => unreachable!(),
+        Err(_) => {
+            println!("Invalid algorithm specified");
+            exit(1);
+        }
+    }
+}
+
+/// Rejects binary and sidewinder on grids other than a rectangular grid of
+/// one level, as clap rejects any other conflicting arguments. They carve
+/// towards the east and the south, which other shapes don't have.
+fn check_algorithm(algorithm: &Result<Algorithm, &'static str>, grid_kind: &Result<GridKind, &'static str>, levels: usize) {
+    let generic = match *grid_kind {
+        Ok(GridKind::Rect) => levels > 1,
+        Ok(GridKind::World) | Err(_) => false,
+        Ok(_) => true
+    };
+
+    match *algorithm {
+        Ok(Algorithm::Binary) | Ok(Algorithm::Sidewinder) if generic => {
+            let message = "The algorithms binary and sidewinder work on rect grids with one level only";
+            clap::Error::with_description(message, clap::ErrorKind::ArgumentConflict).exit()
+        },
+        _ => {}
+    }
+}
+
+/// Maze of the given shape, exiting when it has no cells.
+fn new_maze<S>(shape: S) -> Maze<S>
+    where S: Topology
+{
+    match Maze::try_new(shape) {
+        Ok(maze) => maze,
+        Err(e) => {
+            println!("Invalid size specified: {}", e);
+            exit(1);
+        }
+    }
+}
+
+/// Exits when the image couldn't be written.
+fn write_png(res: Result<(), MazeError>) {
+    if let Err(e) = res {
+        println!("{}", e);
+        exit(1);
+    }
+}
+
+/// Length of the shortest path between the first and the last cell.
+fn solve<M>(maze: &M) -> String
+    where M: Linkable
+{
+    let begin = 0;
+    let end = maze.size() - 1;
+
+    let distances = distance::dijkstra::distances(maze, begin);
+    match distances.get(end) {
+        Some(len) => format!("Shortest path from {} to {} is {} steps long.", begin, end, len),
+        None => format!("There is no path from {} to {}.", begin, end)
+    }
+}
+
+/// Turns `count` random passages into one-way passages leading away from
+/// `begin`, so every cell can still be reached from it.
+fn make_one_way(grid: &mut Grid<BaseCell>, count: usize, begin: (usize, usize)) {
+    let shape = *grid.shape();
+    let distances = distance::dijkstra::distances(grid, shape.id(begin.0, begin.1));
+
+    let mut passages: Vec<(usize, usize)> = Vec::new();
+    for id in 0..grid.size() {
+        for neighbor in grid.linked_ids(id) {
+            if distances.get(neighbor) > distances.get(id) {
+                passages.push((id, neighbor));
+            }
+        }
+    }
+
+    rand::thread_rng().shuffle(&mut passages);
+    for &(from, to) in passages.iter().take(count) {
+        let (x1, y1) = shape.coords(from);
+        let (x2, y2) = shape.coords(to);
+        grid.link_one_way_indices(x1, y1, x2, y2);
+    }
+}
+
+/// Carves the grid and places openings, weights, portals and one-way
+/// passages as given on the command line.
+fn carve(grid: &mut Grid<BaseCell>, matches: &ArgMatches, algorithm: &Result<Algorithm, &'static str>) {
+    let seed = match matches.value_of("seed").map(|seed| seed.parse::<usize>()) {
+        Some(Ok(val)) => Some(val),
+        Some(Err(_)) => {
+            println!("Invalid seed specified");
+            exit(1);
+        },
+        None => None
+    };
+
+    match *algorithm {
+        Ok(Algorithm::AldousBroder) => {
+            info!("Generating maze using Aldous-Broder algorithm");
+            match seed {
+                Some(seed) => grid.generate_aldous_broder_seeded(seed),
+                None => grid.generate_aldous_broder()
+            }
+        },
+        Ok(_) if seed.is_some() => {
+            println!("Seed is supported with aldous-broder algorithm only");
+            exit(1);
+        },
+        Ok(Algorithm::Binary) => {
+            info!("Generating maze using Binary algorithm");
+            grid.generate_binary()
+        },
+        Ok(Algorithm::Sidewinder) => {
+            info!("Generating maze using Sidewinder algorithm");
+            grid.generate_sidewinder()
+        },
+        Ok(Algorithm::Wilson) => {
+            info!("Generating maze using Wilson's algorithm");
+            grid.generate_wilson()
+        },
+        Err(_) => {
+            info!("Invalid algorithm specified");
+            exit(1);
+        }
+    }
+
+    let max_weight = match matches.value_of("weights").unwrap().to_string().parse::<usize>() {
+        Ok(val) if val > 0 => val,
+        _ => {
+            println!("Invalid weights specified");
+            exit(1);
+        }
+    };
+
+    if max_weight > 1 {
+        let mut rng = rand::thread_rng();
+        for x in 0..grid.x() {
+            for y in 0..grid.y() {
+                grid.set_weight(x, y, rng.gen_range(1, max_weight + 1));
+            }
+        }
+    }
+
+    let placement = Placement::from_str(matches.value_of("openings").unwrap());
+    match placement {
+        Ok(Placement::Corners) => grid.place_corners(),
+        Ok(Placement::LongestPath) => {
+            info!("Placing entrance and exit at the ends of the longest path");
+            grid.place_longest_path()
+        },
+        Ok(Placement::Random) => grid.place_random(),
+        Err(_) => {
+            println!("Invalid openings specified");
+            exit(1);
+        }
+    }
+
+    match matches.value_of("portals").unwrap().to_string().parse::<usize>() {
+        Ok(count) => grid.place_portals(count),
+        Err(_) => {
+            println!("Invalid number of portals specified");
+            exit(1);
+        }
+    }
+
+    match matches.value_of("one-way").unwrap().to_string().parse::<usize>() {
+        Ok(0) => {},
+        Ok(count) => {
+            let begin = grid.entrance().map_or((0, 0), |opening| opening.cell());
+            make_one_way(grid, count, begin);
+        },
+        Err(_) => {
+            println!("Invalid number of one-way passages specified");
+            exit(1);
+        }
+    }
+}
+
+/// Reads the maze given by `--input`, or leniently the one given to
+/// `validate`, exiting when it can't be used.
+fn load_grid(filename: &str, lenient: bool) -> Grid<BaseCell> {
+    let mut json = String::new();
+    match File::open(filename).and_then(|mut file| file.read_to_string(&mut json)) {
+        Ok(_) => {},
+        Err(e) => {
+            println!("Unable to read {}: {}", filename, e);
+            exit(1);
+        }
+    }
+
+    let grid = match lenient {
+        true => Grid::from_json_lenient(&json[..]),
+        false => Grid::from_json(&json[..])
+    };
+
+    match grid {
+        Ok(grid) => grid,
+        Err(e) => {
+            println!("Unable to load {}: {}", filename, e);
+            exit(1);
+        }
+    }
+}
+
+/// Reads the graph given by `--graph`, with the links of a maze carved on
+/// it if there are any, exiting when it can't be used.
+fn load_graph(filename: Option<&str>) -> Maze<Graph> {
+    let filename = match filename {
+        Some(filename) => filename,
+        None => {
+            println!("Graph file has to be specified using --graph");
+            exit(1);
+        }
+    };
+
+    let mut json = String::new();
+    match File::open(filename).and_then(|mut file| file.read_to_string(&mut json)) {
+        Ok(_) => {},
+        Err(e) => {
+            println!("Unable to read {}: {}", filename, e);
+            exit(1);
+        }
+    }
+
+    match Maze::from_json(&json[..]) {
+        Ok(maze) => maze,
+        Err(e) => {
+            println!("Invalid graph: {}", e);
+            exit(1);
+        }
+    }
+}
+
+/// Reads the mask given by `--mask`, one row of squares per line, where `X`
+/// marks the squares left out.
+fn load_mask(filename: &str) -> Vec<Vec<bool>> {
+    let mut text = String::new();
+    match File::open(filename).and_then(|mut file| file.read_to_string(&mut text)) {
+        Ok(_) => {},
+        Err(e) => {
+            println!("Unable to read {}: {}", filename, e);
+            exit(1);
+        }
+    }
+
+    let rows: Vec<Vec<bool>> = text.lines().map(|line| line.chars().map(|c| c != 'X').collect()).collect();
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    if width == 0 || !rows.iter().any(|row| row.iter().any(|&inside| inside)) {
+        println!("Mask {} is empty", filename);
+        exit(1);
+    }
+
+    // Short lines are padded with squares left out
+    (0..width).map(|x| rows.iter().map(|row| x < row.len() && row[x]).collect()).collect()
+}
+
 fn main() {
     let default_cell_size = &DEFAULT_CELL_SIZE.to_string()[..];
+    let default_chunk_size = &DEFAULT_CHUNK_SIZE.to_string()[..];
     let default_wall_size = &DEFAULT_WALL_SIZE.to_string()[..];
     let default_height = &DEFAULT_HEIGHT.to_string()[..];
+    let default_levels = &DEFAULT_LEVELS.to_string()[..];
     let default_width = &DEFAULT_WIDTH.to_string()[..];
     let default_port = &DEFAULT_PORT.to_string()[..];
 
@@ -83,7 +453,7 @@ fn main() {
         .version(VERSION)
         .author(AUTHOR)
         .arg(Arg::with_name("algorithm")
-            .help("Algorithm to use")
+            .help("Algorithm to use, binary and sidewinder on rect grids with one level only")
             .short("a")
             .long("algorithm")
             .possible_values(&["aldous-broder", "binary", "sidewinder", "wilson"])
@@ -95,6 +465,11 @@ fn main() {
             .long("cell-size")
             .default_value(default_cell_size)
         )
+        .arg(Arg::with_name("chunk-size")
+            .help("Size of Chunks of infinite Maze")
+            .long("chunk-size")
+            .default_value(default_chunk_size)
+        )
         .arg(Arg::with_name("color-cell")
             .help("Color of Cell")
             .long("color-cell")
@@ -115,9 +490,55 @@ fn main() {
             .help("Output format to use")
             .short("f")
             .long("format")
-            .possible_values(&["ascii", "json", "png"])
+            .possible_values(&["ascii", "json", "png", "svg"])
             .default_value("ascii")
         )
+        .arg(Arg::with_name("levels")
+            .help("Number of Levels of rectangular Maze")
+            .short("l")
+            .long("levels")
+            .default_value(default_levels)
+        )
+        .arg(Arg::with_name("mask")
+            .help("Text file with the shape of a voronoi Maze, X marks squares left out")
+            .long("mask")
+            .takes_value(true)
+        )
+        .arg(Arg::with_name("one-way")
+            .help("Number of one-way passages of rectangular Maze, leading away from the entrance")
+            .long("one-way")
+            .default_value("0")
+        )
+        .arg(Arg::with_name("openings")
+            .help("Placement of entrance and exit")
+            .short("o")
+            .long("openings")
+            .possible_values(&["corners", "longest-path", "random"])
+            .default_value("corners")
+        )
+        .arg(Arg::with_name("origin")
+            .help("Top left Cell of the window into infinite Maze")
+            .long("origin")
+            .default_value("0,0")
+        )
+        .arg(Arg::with_name("grid")
+            .help("Shape of Maze")
+            .short("g")
+            .long("grid")
+            .possible_values(&["cube", "delta", "graph", "hex", "polar", "rect", "upsilon", "voronoi", "world"])
+            .default_value("rect")
+        )
+        .arg(Arg::with_name("graph")
+            .help("JSON file with the nodes and edges of a graph Maze")
+            .long("graph")
+            .takes_value(true)
+        )
+        .arg(Arg::with_name("input")
+            .help("JSON file with a rectangular Maze to render or solve instead of generating one")
+            .short("i")
+            .long("input")
+            .takes_value(true)
+        )
         .arg(Arg::with_name("height")
             .help("Height of Maze")
             .short("y")
@@ -141,17 +562,46 @@ fn main() {
             .short("r")
             .long("rest")
         )
+        .arg(Arg::with_name("seed")
+            .help("Seed of the points of a voronoi Maze, of infinite Maze, or of rectangular Maze carved by aldous-broder")
+            .long("seed")
+            .takes_value(true)
+        )
         .arg(Arg::with_name("solution")
             .help("Show solution")
             .short("s")
             .long("solution")
         )
+        .arg(Arg::with_name("weights")
+            .help("Highest random cost of entering a Cell of rectangular Maze")
+            .long("weights")
+            .default_value("1")
+        )
+        .arg(Arg::with_name("wrap")
+            .help("Edges of rectangular Maze joined together")
+            .long("wrap")
+            .possible_values(&["cylinder", "klein", "mobius", "none", "torus"])
+            .default_value("none")
+        )
+        .arg(Arg::with_name("portals")
+            .help("Number of pairs of Cells of rectangular Maze joined by portals")
+            .long("portals")
+            .default_value("0")
+        )
         .arg(Arg::with_name("rest-port")
             .help("REST Port")
             .short("p")
             .long("rest-port")
             .default_value(default_port)
         )
+        .subcommand(SubCommand::with_name("validate")
+            .about("Checks that a rectangular Maze written as JSON is perfect")
+            .arg(Arg::with_name("FILE")
+                .help("JSON file with the Maze")
+                .required(true)
+                .index(1)
+            )
+        )
         .get_matches();
 
     match matches.occurrences_of("verbose") {
@@ -163,6 +613,15 @@ fn main() {
 
     env_logger::init().unwrap();
 
+    if let Some(matches) = matches.subcommand_matches("validate") {
+        let report = load_grid(matches.value_of("FILE").unwrap(), true).validate();
+        print!("{}", report);
+        exit(match report.is_perfect() {
+            true => 0,
+            false => 1
+        });
+    }
+
     let port: u16 = match matches.value_of("rest-port").unwrap().to_string().parse::<u16>() {
         Ok(val) => val,
         _ => DEFAULT_PORT
@@ -183,6 +642,11 @@ fn main() {
         _ => DEFAULT_WIDTH
     };
 
+    let levels = match matches.value_of("levels").unwrap().to_string().parse::<usize>() {
+        Ok(val) if val > 0 => val,
+        _ => DEFAULT_LEVELS
+    };
+
     let cell_size = match matches.value_of("cell-size").unwrap().to_string().parse::<u32>() {
         Ok(val) => val,
         _ => DEFAULT_CELL_SIZE
@@ -205,38 +669,232 @@ fn main() {
 
     let algorithm = Algorithm::from_str(matches.value_of("algorithm").unwrap());
 
-    let mut grid: Grid<BaseCell> = Grid::new(width, height);
-    match algorithm {
-        Ok(Algorithm::AldousBroder) => {
-            info!("Generating maze using Aldous-Broder algorithm");
-            grid.generate_aldous_broder()
+    let format = Format::from_str(matches.value_of("format").unwrap());
+
+    let grid_kind = GridKind::from_str(matches.value_of("grid").unwrap());
+    check_algorithm(&algorithm, &grid_kind, levels);
+
+    match grid_kind {
+        Ok(GridKind::Rect) if levels > 1 => {
+            let mut maze = new_maze(Levels::new(width, height, levels));
+            generate(&mut maze, &algorithm);
+            if matches.is_present("solution") {
+                println!("{}", solve(&maze));
+            }
+            match format {
+                Ok(Format::Ascii) => maze.print_ascii(),
+                Ok(Format::Png) => write_png(maze.try_to_png(cell_size, wall_size, &color_cell, &color_wall, "output.png")),
+                _ => {
+                    println!("Format is not supported on multi-level grids");
+                    exit(1);
+                }
+            }
+            exit(0);
         },
-        Ok(Algorithm::Binary) => {
-            info!("Generating maze using Binary algorithm");
-            grid.generate_binary()
+        Ok(GridKind::Rect) => {},
+        Ok(GridKind::World) => {
+            let seed = match matches.value_of("seed").map(|seed| seed.parse::<usize>()) {
+                Some(Ok(val)) => val,
+                Some(Err(_)) => {
+                    println!("Invalid seed specified");
+                    exit(1);
+                },
+                None => rand::random::<usize>()
+            };
+
+            let chunk_size = match matches.value_of("chunk-size").unwrap().to_string().parse::<usize>() {
+                Ok(val) if val > 0 => val,
+                _ => DEFAULT_CHUNK_SIZE
+            };
+
+            let origin: Vec<i64> = matches.value_of("origin").unwrap().split(',').filter_map(|c| c.trim().parse::<i64>().ok()).collect();
+            if origin.len() != 2 {
+                println!("Invalid origin specified");
+                exit(1);
+            }
+
+            if width == 0 || height == 0 {
+                println!("Invalid size specified: {}", MazeError::EmptyGrid(width, height));
+                exit(1);
+            }
+
+            info!("Showing {}x{} cells at {},{} of world {}", width, height, origin[0], origin[1], seed);
+            let grid = World::new(seed, chunk_size).viewport(origin[0], origin[1], width, height);
+            match format {
+                Ok(Format::Ascii) => grid.print_ascii(),
+                Ok(Format::Json) => grid.print_json(),
+                Ok(Format::Png) => write_png(grid.try_to_png(cell_size, wall_size, &color_cell, &color_wall, "output.png")),
+                _ => {
+                    println!("Format is not supported on infinite mazes");
+                    exit(1);
+                }
+            }
+            exit(0);
         },
-        Ok(Algorithm::Sidewinder) => {
-            info!("Generating maze using Sidewinder algorithm");
-            grid.generate_sidewinder()
+        Ok(GridKind::Graph) => {
+            // A maze written with -f json is solved or drawn as it is
+            let mut maze = load_graph(matches.value_of("graph"));
+            if maze.links.is_empty() {
+                generate(&mut maze, &algorithm);
+            }
+            if matches.is_present("solution") {
+                match format {
+                    // The document is all there is on stdout, so it can be parsed
+                    Ok(Format::Json) | Ok(Format::Svg) => {
+                        writeln!(&mut io::stderr(), "{}", solve(&maze)).unwrap();
+                    },
+                    _ => println!("{}", solve(&maze))
+                }
+            }
+            match format {
+                Ok(Format::Json) => maze.print_json(),
+                Ok(Format::Png) => write_png(maze.try_to_png(cell_size, wall_size, &color_cell, &color_wall, "output.png")),
+                Ok(Format::Svg) => match maze.try_to_svg(cell_size, wall_size, &color_cell, &color_wall) {
+                    Ok(svg) => print!("{}", svg),
+                    Err(e) => {
+                        println!("{}", e);
+                        exit(1);
+                    }
+                },
+                _ => {
+                    println!("Format is not supported on graphs");
+                    exit(1);
+                }
+            }
+            exit(0);
         },
-        Ok(Algorithm::Wilson) => {
-            info!("Generating maze using Wilson's algorithm");
-            grid.generate_wilson()
+        Ok(_) if format != Ok(Format::Png) => {
+            println!("Format is supported on rectangular grids only");
+            exit(1);
+        },
+        Ok(GridKind::Cube) => {
+            let mut maze = new_maze(Cube::new(width));
+            generate(&mut maze, &algorithm);
+            if matches.is_present("solution") {
+                println!("{}", solve(&maze));
+            }
+            write_png(maze.try_to_png(cell_size, wall_size, &color_cell, &color_wall, "output.png"));
+            exit(0);
+        },
+        Ok(GridKind::Delta) => {
+            let mut maze = new_maze(Delta::new(width, height));
+            generate(&mut maze, &algorithm);
+            if matches.is_present("solution") {
+                println!("{}", solve(&maze));
+            }
+            write_png(maze.try_to_png(cell_size, wall_size, &color_cell, &color_wall, "output.png"));
+            exit(0);
+        },
+        Ok(GridKind::Hex) => {
+            let mut maze = new_maze(Hex::new(width, height));
+            generate(&mut maze, &algorithm);
+            if matches.is_present("solution") {
+                println!("{}", solve(&maze));
+            }
+            write_png(maze.try_to_png(cell_size, wall_size, &color_cell, &color_wall, "output.png"));
+            exit(0);
+        },
+        Ok(GridKind::Polar) => {
+            let mut maze = new_maze(Polar::new(height));
+            generate(&mut maze, &algorithm);
+            if matches.is_present("solution") {
+                println!("{}", solve(&maze));
+            }
+            write_png(maze.try_to_png(cell_size, wall_size, &color_cell, &color_wall, "output.png"));
+            exit(0);
+        },
+        Ok(GridKind::Upsilon) => {
+            let mut maze = new_maze(Upsilon::new(width, height));
+            generate(&mut maze, &algorithm);
+            if matches.is_present("solution") {
+                println!("{}", solve(&maze));
+            }
+            write_png(maze.try_to_png(cell_size, wall_size, &color_cell, &color_wall, "output.png"));
+            exit(0);
+        },
+        Ok(GridKind::Voronoi) => {
+            let seed = match matches.value_of("seed").map(|seed| seed.parse::<usize>()) {
+                Some(Ok(val)) => val,
+                Some(Err(_)) => {
+                    println!("Invalid seed specified");
+                    exit(1);
+                },
+                None => rand::random::<usize>()
+            };
+            info!("Placing points using seed {}", seed);
+
+            let voronoi = match matches.value_of("mask") {
+                Some(filename) => Voronoi::with_mask(&load_mask(filename), seed),
+                None => Voronoi::new(width, height, seed)
+            };
+
+            let mut maze = new_maze(voronoi);
+            generate(&mut maze, &algorithm);
+            if matches.is_present("solution") {
+                println!("{}", solve(&maze));
+            }
+            write_png(maze.try_to_png(cell_size, wall_size, &color_cell, &color_wall, "output.png"));
+            exit(0);
         },
         Err(_) => {
-            info!("Invalid algorithm specified");
+            println!("Invalid grid specified");
             exit(1);
         }
     }
 
-    let format = Format::from_str(matches.value_of("format").unwrap());
+    let wrap = match WrapKind::from_str(matches.value_of("wrap").unwrap()) {
+        Ok(WrapKind::Cylinder) => Wrap::Cylinder,
+        Ok(WrapKind::Klein) => Wrap::Klein,
+        Ok(WrapKind::Mobius) => Wrap::Mobius,
+        Ok(WrapKind::None) => Wrap::None,
+        Ok(WrapKind::Torus) => Wrap::Torus,
+        Err(_) => {
+            println!("Invalid wrap specified");
+            exit(1);
+        }
+    };
+
+    let grid = match matches.value_of("input") {
+        Some(filename) => load_grid(filename, false),
+        None => {
+            let mut grid: Grid<BaseCell> = match Grid::try_with_wrap(width, height, wrap) {
+                Ok(grid) => grid,
+                Err(e) => {
+                    println!("Invalid size specified: {}", e);
+                    exit(1);
+                }
+            };
+
+            carve(&mut grid, &matches, &algorithm);
+            grid
+        }
+    };
+
+    let max_weight = grid.max_weight();
+
     match format {
         Ok(Format::Ascii) => grid.print_ascii(),
-        Ok(Format::Json) => grid.print_json(),
+        Ok(Format::Json) => {
+            // The solution goes into the document, which is all there is to print
+            if matches.is_present("solution") {
+                println!("{}", grid.to_json_with_solution());
+                exit(0);
+            }
+            grid.print_json()
+        },
         Ok(Format::Png) => {
             let output_filename = "output.png";
             info!("Writing maze to {:?}", output_filename);
-            grid.to_png(cell_size, wall_size, &color_cell, &color_wall, output_filename);
+            let res = match max_weight > 1 {
+                true => grid.try_to_png_weights(cell_size, wall_size, &color_cell, &color_wall, output_filename),
+                false => grid.try_to_png(cell_size, wall_size, &color_cell, &color_wall, output_filename)
+            };
+
+            write_png(res);
+        },
+        Ok(Format::Svg) => {
+            println!("Format is supported on graphs only");
+            exit(1);
         },
         Err(_) => {
             println!("Invalid format specified");
@@ -247,17 +905,40 @@ fn main() {
     match matches.occurrences_of("solution") {
         0 => {},
         _ => {
+            // Mazes without an outer wall have no openings
+            let begin = grid.entrance().map_or((0, 0), |opening| opening.cell());
+            let end = grid.exit().map_or((grid.x() - 1, grid.y() - 1), |opening| opening.cell());
+
             println!("Solution");
-            let distances = distance::dijkstra::calculate(&grid, (0, 0), (grid.x() -1 , grid.y() - 1));
-            distances.print_ascii();
-            let len = distances[distances.x() - 1][distances.y() - 1].distance().unwrap();
+            let distances = match distance::dijkstra::try_calculate(&grid, begin, end) {
+                Ok(distances) => distances,
+                Err(e) => {
+                    println!("No solution: {}", e);
+                    exit(1);
+                }
+            };
+            distances.print_solution();
+            let len = distances[end.0][end.1].distance().unwrap();
             info!("Shortest path is {} steps long.", len);
 
             println!("Solution (Reversed)");
-            let distances = distance::dijkstra::calculate(&grid, (grid.x() -1 , grid.y() - 1), (0, 0));
-            distances.print_ascii();
+            let distances = distance::dijkstra::calculate(&grid, end, begin);
+            distances.print_solution();
             info!("Shortest path is {} steps long.", len);
 
+            if max_weight > 1 {
+                println!("Solution (Fastest)");
+                let distances = match distance::dijkstra::try_calculate_weighted(&grid, begin, end) {
+                    Ok(distances) => distances,
+                    Err(e) => {
+                        println!("No solution: {}", e);
+                        exit(1);
+                    }
+                };
+                distances.print_solution();
+                info!("Fastest path costs {}.", distances[end.0][end.1].distance().unwrap());
+            }
+
             // info!("Let see paths {} steps long", len / 2);
         }
     }
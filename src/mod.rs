@@ -1,5 +1,9 @@
 pub mod distance;
+pub mod error;
 pub mod generator;
 pub mod output;
+pub mod placement;
+pub mod transform;
 pub mod types;
+pub mod validation;
 pub mod web;
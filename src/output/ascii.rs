@@ -1,30 +1,149 @@
-use std::iter;
+use ansi_term::Colour::{Green, Black};
+use ansi_term::Style;
 
+use std::collections::HashMap;
+
+use super::super::distance::dijkstra::DistanceCell;
 use super::super::types::cell::Cell;
 use super::super::types::grid::Grid;
+use super::super::types::rectangle::{Direction, Wrap};
+
+const EMPTY_CELL: &'static str = "   ";
+
+static ASCII_LOWER: [char; 62] = [
+'0', '1', '2', '3', '4', '5', '6', '7', '8', '9',
+'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j',
+'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't',
+'u', 'v', 'w', 'x', 'y', 'z', 'A', 'B', 'C', 'D',
+'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N',
+'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X',
+'Y', 'Z'
+];
+
+/// Labels of portals, both ends of a portal get the same one. They repeat
+/// when there are more portals.
+static PORTAL_LABELS: &'static [u8] = b"123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Label of every cell which is an end of a portal.
+fn portal_labels<T>(grid: &Grid<T>) -> HashMap<(usize, usize), char>
+    where T: Cell + Clone
+{
+    let mut res = HashMap::new();
+    for (i, &(from, to)) in grid.portals().iter().enumerate() {
+        let label = PORTAL_LABELS[i % PORTAL_LABELS.len()] as char;
+        res.insert(from, label);
+        res.insert(to, label);
+    }
+
+    res
+}
+
+/// Surrounds the maze with arrows marking which edges are joined, as in
+/// textbook diagrams - `v` on joined west and east edges (`^` where they are
+/// joined upside down) and `>>` on joined north and south edges.
+fn mark_seams(maze: &str, x: usize, wrap: Wrap) -> String {
+    let (left, right) = match (wrap.wraps_x(), wrap.flips_x()) {
+        (false, _) => ("", ""),
+        (true, false) => ("v ", " v"),
+        (true, true) => ("v ", " ^")
+    };
+
+    let padding = &"  "[..left.len()];
+    let mut seam = padding.to_string() + " ";
+    for _ in 0..x {
+        seam += ">>  ";
+    }
+    seam += "\n";
+
+    let mut res = String::new();
+    if wrap.wraps_y() {
+        res += &seam[..];
+    }
+
+    for (i, line) in maze.lines().enumerate() {
+        match i % 2 {
+            1 => res += &format!("{}{}{}\n", left, line, right)[..],
+            _ => res += &format!("{}{}\n", padding, line)[..]
+        }
+    }
+
+    if wrap.wraps_y() {
+        res += &seam[..];
+    }
+
+    return res;
+}
+
+/// Marking of the passage from cell (x, y) to its neighbour, `forward` or
+/// `backward` where it can be passed one way only.
+fn one_way<T>(grid: &Grid<T>, x: usize, y: usize, neighbor: Option<(usize, usize)>, forward: &'static str, backward: &'static str, open: &'static str) -> &'static str
+    where T: Cell + Clone
+{
+    match neighbor {
+        Some((nx, ny)) if grid.is_one_way_indices(x, y, nx, ny) => forward,
+        Some((nx, ny)) if grid.is_one_way_indices(nx, ny, x, y) => backward,
+        _ => open
+    }
+}
 
 pub fn format<T>(grid: &Grid<T>) -> String
     where T: Cell + Clone
 {
+    format_with(grid, |_| EMPTY_CELL.to_string())
+}
+
+/// Maze with the distances of the cells on the path highlighted.
+pub fn format_distances(grid: &Grid<DistanceCell>) -> String {
+    format_with(grid, |cell| {
+        match cell.distance() {
+            Some(d) if cell.is_path() => {
+                Style::new().on(Green).fg(Black).paint(format!(" {} ", ASCII_LOWER[d % 62])).to_string()
+                // Green.paint(format!(" {} ", ASCII_LOWER[d % 62])).to_string()
+            },
+            _ => EMPTY_CELL.to_string()
+        }
+    })
+}
+
+/// Maze with every cell labelled by `label`, which should return three
+/// characters. Ends of portals are labelled as such instead.
+pub fn format_with<T, F>(grid: &Grid<T>, label: F) -> String
+    where T: Cell + Clone, F: Fn(&T) -> String
+{
+    let portals = portal_labels(grid);
+
     let mut res = String::new();
     res += "+";
-    res += &iter::repeat("---+").take(grid.x()).collect::<String>()[..];
+    res += &(0..grid.x()).map(|x| {
+        match grid.is_opening(x, 0, Direction::North) || grid.is_open((x, 0), Direction::North) {
+            true => "   +",
+            false => "---+"
+        }
+    }).collect::<String>()[..];
     res += "\n";
 
     for y in 0..grid.y() {
-        let mut top = "|".to_string();
+        let mut top = match grid.is_opening(0, y, Direction::West) || grid.is_open((0, y), Direction::West) {
+            true => " ".to_string(),
+            false => "|".to_string()
+        };
         let mut bottom = "+".to_string();
 
         for x in 0..grid.x() {
-            top += &grid.cells[x][y].to_string()[..];
+            match portals.get(&(x, y)) {
+                Some(label) => top += &format!("({})", label)[..],
+                None => top += &label(&grid.cells[x][y])[..]
+            }
 
-            match grid.is_linked_indices(x, y, x + 1, y) {
-                true => top += " ",
+            let east = grid.neighbor((x, y), Direction::East);
+            match grid.is_open((x, y), Direction::East) || grid.is_opening(x, y, Direction::East) {
+                true => top += one_way(grid, x, y, east, ">", "<", " "),
                 false => top += "|"
             }
 
-            match grid.is_linked_indices(x, y, x, y + 1) {
-                true => bottom += "   +",
+            let south = grid.neighbor((x, y), Direction::South);
+            match grid.is_open((x, y), Direction::South) || grid.is_opening(x, y, Direction::South) {
+                true => bottom += one_way(grid, x, y, south, " v +", " ^ +", "   +"),
                 false => bottom += "---+",
             }
         }
@@ -36,5 +155,8 @@ pub fn format<T>(grid: &Grid<T>) -> String
         res += "\n";
     }
 
-    return res;
+    match grid.shape().wrap() {
+        Wrap::None => res,
+        wrap => mark_seams(&res[..], grid.x(), wrap)
+    }
 }
@@ -0,0 +1,67 @@
+use super::super::types::levels::{LevelDirection, Levels};
+use super::super::types::maze::Maze;
+
+/// Marker of the stairs leading out of a cell.
+fn stairs(maze: &Maze<Levels>, x: usize, y: usize, z: usize) -> &'static str {
+    let up = maze.is_open(x, y, z, LevelDirection::Up);
+    let down = maze.is_open(x, y, z, LevelDirection::Down);
+
+    match (up, down) {
+        (true, true) => "^ v",
+        (true, false) => " ^ ",
+        (false, true) => " v ",
+        (false, false) => "   "
+    }
+}
+
+fn format_level(maze: &Maze<Levels>, z: usize) -> Vec<String> {
+    let levels = maze.shape();
+    let mut lines = Vec::new();
+
+    let width = levels.x() * 4 + 1;
+    lines.push(format!("{:<width$}", format!("Level {}", z), width = width));
+
+    let mut top = "+".to_string();
+    for _ in 0..levels.x() {
+        top += "---+";
+    }
+    lines.push(top);
+
+    for y in 0..levels.y() {
+        let mut middle = "|".to_string();
+        let mut bottom = "+".to_string();
+
+        for x in 0..levels.x() {
+            middle += stairs(maze, x, y, z);
+
+            match maze.is_open(x, y, z, LevelDirection::East) {
+                true => middle += " ",
+                false => middle += "|"
+            }
+
+            match maze.is_open(x, y, z, LevelDirection::South) {
+                true => bottom += "   +",
+                false => bottom += "---+"
+            }
+        }
+
+        lines.push(middle);
+        lines.push(bottom);
+    }
+
+    lines
+}
+
+/// Levels side by side, `^` and `v` mark stairs up and down.
+pub fn format(maze: &Maze<Levels>) -> String {
+    let levels: Vec<Vec<String>> = (0..maze.shape().z()).map(|z| format_level(maze, z)).collect();
+
+    let mut res = String::new();
+    for line in 0..levels[0].len() {
+        let row: Vec<&str> = levels.iter().map(|level| &level[line][..]).collect();
+        res += &row.join("  ")[..];
+        res += "\n";
+    }
+
+    return res;
+}
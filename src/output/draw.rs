@@ -0,0 +1,63 @@
+extern crate image;
+extern crate imageproc;
+
+use image::{Rgb, RgbImage};
+use imageproc::rect::Rect;
+use imageproc::drawing::draw_filled_rect_mut;
+
+use super::super::error::MazeError;
+
+/// Draws a wall of the given thickness between two points by stamping
+/// squares along the segment, which also rounds off the joints.
+pub fn draw_wall(img: &mut RgbImage, start: (f32, f32), end: (f32, f32), thickness: u32, color: Rgb<u8>) {
+    let dx = end.0 - start.0;
+    let dy = end.1 - start.1;
+    let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as u32;
+    let half = thickness as f32 / 2.0;
+
+    for i in 0..(steps + 1) {
+        let t = i as f32 / steps as f32;
+        let x = (start.0 + dx * t - half).round() as i32;
+        let y = (start.1 + dy * t - half).round() as i32;
+        draw_filled_rect_mut(img, Rect::at(x, y).of_size(thickness.max(1), thickness.max(1)), color);
+    }
+}
+
+/// Image of the given size filled with the cell color.
+pub fn new_image(img_x: u32, img_y: u32, color_cell: &[u8; 3]) -> RgbImage {
+    let mut img = RgbImage::new(img_x, img_y);
+    draw_filled_rect_mut(&mut img, Rect::at(0, 0).of_size(img_x, img_y), Rgb(*color_cell));
+    img
+}
+
+/// Writes the image, failing when it can't be written.
+pub fn save(img: &RgbImage, output_filename: &str) -> Result<(), MazeError> {
+    match img.save(output_filename) {
+        Ok(()) => Ok(()),
+        Err(e) => Err(MazeError::Output(format!("{}: {}", output_filename, e)))
+    }
+}
+
+/// Draws a line from `start` to `end` with `heads` arrow heads in its middle
+/// pointing towards `end`.
+pub fn draw_arrow(img: &mut RgbImage, start: (f32, f32), end: (f32, f32), heads: u32, size: f32, thickness: u32, color: Rgb<u8>) {
+    draw_wall(img, start, end, thickness, color);
+
+    let length = ((end.0 - start.0).powi(2) + (end.1 - start.1).powi(2)).sqrt();
+    if length == 0.0 {
+        return;
+    }
+
+    let direction = ((end.0 - start.0) / length, (end.1 - start.1) / length);
+    let middle = ((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0);
+
+    for i in 0..heads {
+        let offset = (i as f32 - (heads as f32 - 1.0) / 2.0) * size;
+        let tip = (middle.0 + direction.0 * (offset + size / 2.0), middle.1 + direction.1 * (offset + size / 2.0));
+        let back = (tip.0 - direction.0 * size, tip.1 - direction.1 * size);
+
+        // Barbs are perpendicular to the line, one on each side
+        draw_wall(img, tip, (back.0 - direction.1 * size, back.1 + direction.0 * size), thickness, color);
+        draw_wall(img, tip, (back.0 + direction.1 * size, back.1 - direction.0 * size), thickness, color);
+    }
+}
@@ -1,39 +1,140 @@
 extern crate serde_json;
 
-use serde_json::Map;
+use std::collections::HashSet;
 
+use serde_json::{Map, Value};
+
+use super::super::distance;
+use super::super::error::MazeError;
 use super::super::types::cell::Cell;
 use super::super::types::grid::Grid;
+use super::super::types::opening::Opening;
+use super::super::types::rectangle::{Direction, Wrap};
 
-pub fn format<T>(grid: &Grid<T>) -> String
+/// Version of the document written by `format`. Documents without a
+/// `version` are version 1, which had the size, links and openings only.
+pub const VERSION: u64 = 2;
+
+/// Most cells of a document read by `parse`, so a mistyped size is rejected
+/// rather than running out of memory.
+pub const MAX_CELLS: usize = 1 << 26;
+
+type Link = ((usize, usize), (usize, usize));
+
+fn format_cell(cell: (usize, usize)) -> serde_json::value::Value {
+    let mut pair: Vec<serde_json::value::Value> = Vec::new();
+    pair.push(serde_json::value::Value::U64(cell.0 as u64));
+    pair.push(serde_json::value::Value::U64(cell.1 as u64));
+    serde_json::value::Value::Array(pair)
+}
+
+/// Weights and metadata of every cell which has any.
+fn format_cells<T>(grid: &Grid<T>) -> serde_json::value::Value
     where T: Cell + Clone
 {
-    let mut map: Map<String, serde_json::Value> = Map::new();
-    let mut links: Vec<serde_json::value::Value> = Vec::new();
+    let mut cells: Vec<serde_json::value::Value> = Vec::new();
+    for y in 0..grid.y() {
+        for x in 0..grid.x() {
+            let weight = grid.weight(x, y);
+            let metadata = grid.metadata(x, y);
+            if weight == 1 && metadata.is_none() {
+                continue;
+            }
 
-    for (k, set) in grid.links.iter() {
-        for v in set.iter() {
-            let mut first: Vec<serde_json::value::Value> = Vec::new();
-            first.push(serde_json::value::Value::U64(k.0 as u64));
-            first.push(serde_json::value::Value::U64(k.1 as u64));
+            let mut cell: Map<String, serde_json::Value> = Map::new();
+            cell.insert("x".to_string(), serde_json::value::Value::U64(x as u64));
+            cell.insert("y".to_string(), serde_json::value::Value::U64(y as u64));
+            if weight > 1 {
+                cell.insert("weight".to_string(), serde_json::value::Value::U64(weight as u64));
+            }
+            if let Some(metadata) = metadata {
+                let values = metadata.iter().map(|(k, v)| (k.clone(), serde_json::value::Value::String(v.clone()))).collect();
+                cell.insert("metadata".to_string(), serde_json::value::Value::Object(values));
+            }
+            cells.push(serde_json::value::Value::Object(cell));
+        }
+    }
 
-            let mut second: Vec<serde_json::value::Value> = Vec::new();
-            second.push(serde_json::value::Value::U64(v.0 as u64));
-            second.push(serde_json::value::Value::U64(v.1 as u64));
+    serde_json::value::Value::Array(cells)
+}
 
-            let mut tuple: Vec<serde_json::value::Value> = Vec::new();
-            tuple.push(serde_json::value::Value::Array(first));
-            tuple.push(serde_json::value::Value::Array(second));
+fn format_link(link: Link) -> serde_json::value::Value {
+    let mut tuple: Vec<serde_json::value::Value> = Vec::new();
+    tuple.push(format_cell(link.0));
+    tuple.push(format_cell(link.1));
+    serde_json::value::Value::Array(tuple)
+}
 
-            links.push(serde_json::value::Value::Array(tuple));
-        }
+/// Links with a cost of their own, as `[[x1, y1], [x2, y2], weight]`.
+fn format_link_weights<T>(grid: &Grid<T>) -> serde_json::value::Value
+    where T: Cell + Clone
+{
+    let link_weights = grid.link_weights().into_iter().map(|((a, b), weight)| {
+        let mut tuple: Vec<serde_json::value::Value> = Vec::new();
+        tuple.push(format_cell(a));
+        tuple.push(format_cell(b));
+        tuple.push(serde_json::value::Value::U64(weight as u64));
+        serde_json::value::Value::Array(tuple)
+    }).collect();
+
+    serde_json::value::Value::Array(link_weights)
+}
+
+fn format_opening(opening: Option<Opening>) -> serde_json::value::Value {
+    match opening {
+        Some(opening) => {
+            let mut map: Map<String, serde_json::Value> = Map::new();
+            map.insert("x".to_string(), serde_json::value::Value::U64(opening.x as u64));
+            map.insert("y".to_string(), serde_json::value::Value::U64(opening.y as u64));
+            map.insert("side".to_string(), serde_json::value::Value::String(opening.side.name().to_string()));
+            serde_json::value::Value::Object(map)
+        },
+        None => serde_json::value::Value::Null
     }
+}
+
+fn document<T>(grid: &Grid<T>) -> Map<String, serde_json::Value>
+    where T: Cell + Clone
+{
+    let mut map: Map<String, serde_json::Value> = Map::new();
+    let pairs = grid.links.pairs();
+
+    let links = pairs.iter().map(|&link| format_link(link)).collect();
+    let one_way = pairs.iter()
+        .filter(|&&(from, to)| grid.is_one_way_indices(from.0, from.1, to.0, to.1))
+        .map(|&link| format_link(link))
+        .collect();
 
+    let algorithm = match grid.algorithm() {
+        Some(algorithm) => serde_json::value::Value::String(algorithm.to_string()),
+        None => serde_json::value::Value::Null
+    };
+
+    let seed = match grid.seed() {
+        Some(seed) => serde_json::value::Value::U64(seed as u64),
+        None => serde_json::value::Value::Null
+    };
+
+    map.insert("version".to_string(), serde_json::value::Value::U64(VERSION));
+    map.insert("kind".to_string(), serde_json::value::Value::String("rect".to_string()));
+    map.insert("wrap".to_string(), serde_json::value::Value::String(grid.shape().wrap().name().to_string()));
     map.insert("x".to_string(), serde_json::value::Value::U64(grid.x() as u64));
     map.insert("y".to_string(), serde_json::value::Value::U64(grid.y() as u64));
+    map.insert("algorithm".to_string(), algorithm);
+    map.insert("seed".to_string(), seed);
     map.insert("links".to_string(), serde_json::value::Value::Array(links));
+    map.insert("one_way".to_string(), serde_json::value::Value::Array(one_way));
+    map.insert("entrance".to_string(), format_opening(grid.entrance()));
+    map.insert("exit".to_string(), format_opening(grid.exit()));
+    map.insert("mask".to_string(), serde_json::value::Value::Array(grid.mask().into_iter().map(format_cell).collect()));
+    map.insert("cells".to_string(), format_cells(grid));
+    map.insert("link_weights".to_string(), format_link_weights(grid));
+
+    map
+}
 
-    match serde_json::to_string(&map) {
+fn to_string(map: &Map<String, serde_json::Value>) -> String {
+    match serde_json::to_string(map) {
         Ok(json) => {
             return json;
         },
@@ -41,4 +142,438 @@ pub fn format<T>(grid: &Grid<T>) -> String
             return String::new()
         }
     }
-}
\ No newline at end of file
+}
+
+/// Every link is listed in `links` in the direction it can be passed, so a
+/// two-way passage is listed twice. One-way passages are listed once more in
+/// `one_way`, telling them apart from a broken document.
+pub fn format<T>(grid: &Grid<T>) -> String
+    where T: Cell + Clone
+{
+    to_string(&document(grid))
+}
+
+/// Same as `format`, with the cells of the shortest path from the entrance
+/// to the exit (or between opposite corners) in `solution`, empty when there
+/// is none.
+pub fn format_with_solution<T>(grid: &Grid<T>) -> String
+    where T: Cell + Clone
+{
+    let shape = *grid.shape();
+    let begin = grid.entrance().map_or((0, 0), |opening| opening.cell());
+    let end = grid.exit().map_or((grid.x() - 1, grid.y() - 1), |opening| opening.cell());
+
+    let distances = distance::dijkstra::distances(grid, shape.id(begin.0, begin.1));
+    let path = distances.path_to(shape.id(end.0, end.1));
+
+    let mut map = document(grid);
+    map.insert("solution".to_string(), serde_json::value::Value::Array(path.into_iter().map(|id| format_cell(shape.coords(id))).collect()));
+    to_string(&map)
+}
+
+fn invalid(reason: String) -> MazeError {
+    MazeError::Input(reason)
+}
+
+/// Cell given as `[x, y]`.
+fn parse_cell(value: &Value) -> Option<(usize, usize)> {
+    match value.as_array() {
+        Some(pair) if pair.len() == 2 => match (pair[0].as_u64(), pair[1].as_u64()) {
+            (Some(x), Some(y)) => Some((x as usize, y as usize)),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+/// Weights and metadata of cells, given as `{"x", "y", "weight", "metadata"}`.
+fn parse_cells<T>(grid: &mut Grid<T>, value: Option<&Value>) -> Result<(), MazeError>
+    where T: Cell + Clone
+{
+    let values = match value {
+        Some(&Value::Array(ref values)) => values,
+        None => return Ok(()),
+        _ => return Err(invalid("\"cells\" is not an array".to_string()))
+    };
+
+    for value in values.iter() {
+        let object = match value.as_object() {
+            Some(object) => object,
+            None => return Err(invalid(format!("cell is not an object: {:?}", value)))
+        };
+
+        let (x, y) = match (object.get("x").and_then(|x| x.as_u64()), object.get("y").and_then(|y| y.as_u64())) {
+            (Some(x), Some(y)) => (x as usize, y as usize),
+            _ => return Err(invalid(format!("cell needs \"x\" and \"y\": {:?}", object)))
+        };
+
+        if let Err(e) = grid.check_indices(x, y) {
+            return Err(e);
+        }
+
+        match object.get("weight").map(|weight| weight.as_u64()) {
+            Some(Some(weight)) if weight > 0 => grid.set_weight(x, y, weight as usize),
+            Some(_) => return Err(invalid(format!("weight of cell ({}, {}) is not a positive integer", x, y))),
+            None => {}
+        }
+
+        match object.get("metadata") {
+            Some(&Value::Object(ref metadata)) => {
+                for (key, value) in metadata.iter() {
+                    match value.as_str() {
+                        Some(value) => grid.set_metadata(x, y, &key[..], value),
+                        None => return Err(invalid(format!("metadata {:?} of cell ({}, {}) is not a string", key, x, y)))
+                    }
+                }
+            },
+            Some(_) => return Err(invalid(format!("metadata of cell ({}, {}) is not an object", x, y))),
+            None => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Costs of links, given as `[[x1, y1], [x2, y2], weight]`.
+fn parse_link_weights<T>(grid: &mut Grid<T>, value: Option<&Value>) -> Result<(), MazeError>
+    where T: Cell + Clone
+{
+    let values = match value {
+        Some(&Value::Array(ref values)) => values,
+        None => return Ok(()),
+        _ => return Err(invalid("\"link_weights\" is not an array".to_string()))
+    };
+
+    for value in values.iter() {
+        let link_weight = match value.as_array() {
+            Some(tuple) if tuple.len() == 3 => (parse_cell(&tuple[0]), parse_cell(&tuple[1]), tuple[2].as_u64()),
+            _ => (None, None, None)
+        };
+
+        let (a, b, weight) = match link_weight {
+            (Some(a), Some(b), Some(weight)) => (a, b, weight as usize),
+            _ => return Err(invalid(format!("link weight is not a pair of cells and a weight: {:?}", value)))
+        };
+
+        if let Err(e) = grid.try_set_link_weight(a.0, a.1, b.0, b.1, weight) {
+            return Err(e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Links given as `[[x1, y1], [x2, y2]]`, checked to lie inside the grid
+/// and, if `strict`, to join two different cells.
+fn parse_links<T>(grid: &Grid<T>, value: Option<&Value>, name: &str, strict: bool) -> Result<Vec<Link>, MazeError>
+    where T: Cell + Clone
+{
+    let values = match value {
+        Some(&Value::Array(ref values)) => values,
+        None => return Ok(Vec::new()),
+        _ => return Err(invalid(format!("\"{}\" is not an array", name)))
+    };
+
+    let mut res = Vec::with_capacity(values.len());
+    for value in values.iter() {
+        let link = match value.as_array() {
+            Some(pair) if pair.len() == 2 => (parse_cell(&pair[0]), parse_cell(&pair[1])),
+            _ => (None, None)
+        };
+
+        let (from, to) = match link {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Err(invalid(format!("link is not a pair of cells: {:?}", value)))
+        };
+
+        if let Err(e) = grid.check_indices(from.0, from.1).and(grid.check_indices(to.0, to.1)) {
+            return Err(e);
+        }
+
+        if strict && from == to {
+            return Err(invalid(format!("link from ({}, {}) to itself", from.0, from.1)));
+        }
+
+        res.push((from, to));
+    }
+
+    Ok(res)
+}
+
+/// Cells left out of the maze, given as `[x, y]`.
+fn parse_mask<T>(grid: &mut Grid<T>, value: Option<&Value>) -> Result<(), MazeError>
+    where T: Cell + Clone
+{
+    let values = match value {
+        Some(&Value::Array(ref values)) => values,
+        None => return Ok(()),
+        _ => return Err(invalid("\"mask\" is not an array".to_string()))
+    };
+
+    for value in values.iter() {
+        let (x, y) = match parse_cell(value) {
+            Some(cell) => cell,
+            None => return Err(invalid(format!("masked cell is not a pair of coordinates: {:?}", value)))
+        };
+
+        if let Err(e) = grid.check_indices(x, y) {
+            return Err(e);
+        }
+
+        grid.set_masked(x, y, true);
+    }
+
+    Ok(())
+}
+
+fn parse_opening<T>(grid: &Grid<T>, value: Option<&Value>) -> Result<Option<Opening>, MazeError>
+    where T: Cell + Clone
+{
+    let object = match value {
+        None | Some(&Value::Null) => return Ok(None),
+        Some(&Value::Object(ref object)) => object,
+        Some(value) => return Err(invalid(format!("opening is not an object: {:?}", value)))
+    };
+
+    let x = object.get("x").and_then(|x| x.as_u64());
+    let y = object.get("y").and_then(|y| y.as_u64());
+    let side = object.get("side").and_then(|side| side.as_str()).and_then(Direction::from_name);
+    match (x, y, side) {
+        (Some(x), Some(y), Some(side)) => {
+            match grid.check_indices(x as usize, y as usize) {
+                Ok(()) => Ok(Some(Opening::new(x as usize, y as usize, side))),
+                Err(e) => Err(e)
+            }
+        },
+        _ => Err(invalid(format!("opening needs \"x\", \"y\" and \"side\": {:?}", object)))
+    }
+}
+
+/// Every link needs a way back unless it is listed in `one_way`, and may not
+/// touch a masked cell.
+fn check_links<T>(grid: &Grid<T>, links: &[Link], one_way: &HashSet<Link>) -> Result<(), MazeError>
+    where T: Cell + Clone
+{
+    let all: HashSet<Link> = links.iter().cloned().collect();
+    for &(from, to) in links.iter() {
+        if !all.contains(&(to, from)) && !one_way.contains(&(from, to)) {
+            return Err(invalid(format!("link from ({}, {}) to ({}, {}) has no way back", from.0, from.1, to.0, to.1)));
+        }
+
+        if grid.is_masked(from.0, from.1) || grid.is_masked(to.0, to.1) {
+            return Err(invalid(format!("link from ({}, {}) to ({}, {}) touches a masked cell", from.0, from.1, to.0, to.1)));
+        }
+    }
+
+    for &(from, to) in one_way.iter() {
+        if !all.contains(&(from, to)) || all.contains(&(to, from)) {
+            return Err(invalid(format!("one-way link from ({}, {}) to ({}, {}) doesn't match the links", from.0, from.1, to.0, to.1)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads back a maze written by `format`, of any version up to `VERSION`.
+/// Every link has to join two cells of the grid and have a way back, unless
+/// it is listed in `one_way`, and no link may lead to a masked cell. Link
+/// weights need cells of the grid and a weight of at least 1. The
+/// `solution` is left out, it can be found again.
+pub fn parse<T>(json: &str) -> Result<Grid<T>, MazeError>
+    where T: Cell + Clone
+{
+    parse_document(json, true)
+}
+
+/// Same as `parse`, checking only that links lie inside the grid, so a
+/// broken maze can be loaded to be validated. Links without a way back,
+/// links to masked cells and links of a cell to itself are kept as they are.
+pub fn parse_lenient<T>(json: &str) -> Result<Grid<T>, MazeError>
+    where T: Cell + Clone
+{
+    parse_document(json, false)
+}
+
+fn parse_document<T>(json: &str, strict: bool) -> Result<Grid<T>, MazeError>
+    where T: Cell + Clone
+{
+    let document: Value = match serde_json::from_str(json) {
+        Ok(document) => document,
+        Err(e) => return Err(invalid(format!("invalid JSON: {}", e)))
+    };
+
+    let object = match document.as_object() {
+        Some(object) => object,
+        None => return Err(invalid("document is not an object".to_string()))
+    };
+
+    match object.get("version").map(|version| version.as_u64()) {
+        None => {},
+        Some(Some(version)) if version >= 1 && version <= VERSION => {},
+        Some(_) => return Err(invalid(format!("unsupported version {:?}", object["version"])))
+    }
+
+    match object.get("kind").map(|kind| kind.as_str()) {
+        None | Some(Some("rect")) => {},
+        Some(_) => return Err(invalid(format!("unsupported kind {:?}", object["kind"])))
+    }
+
+    let wrap = match object.get("wrap").map(|wrap| wrap.as_str().and_then(Wrap::from_name)) {
+        None => Wrap::None,
+        Some(Some(wrap)) => wrap,
+        Some(None) => return Err(invalid(format!("unknown wrap {:?}", object["wrap"])))
+    };
+
+    let (x, y) = match (object.get("x").and_then(|x| x.as_u64()), object.get("y").and_then(|y| y.as_u64())) {
+        (Some(x), Some(y)) => (x as usize, y as usize),
+        _ => return Err(invalid("missing size \"x\" and \"y\"".to_string()))
+    };
+
+    match x.checked_mul(y) {
+        Some(cells) if cells <= MAX_CELLS => {},
+        _ => return Err(invalid(format!("size {}x{} is over {} cells", x, y, MAX_CELLS)))
+    }
+
+    let mut grid: Grid<T> = match Grid::try_with_wrap(x, y, wrap) {
+        Ok(grid) => grid,
+        Err(e) => return Err(e)
+    };
+
+    match object.get("algorithm") {
+        None | Some(&Value::Null) => {},
+        Some(&Value::String(ref algorithm)) => grid.set_algorithm(Some(algorithm.clone())),
+        Some(value) => return Err(invalid(format!("algorithm is not a string: {:?}", value)))
+    }
+
+    match object.get("seed") {
+        None | Some(&Value::Null) => {},
+        Some(&Value::U64(seed)) => grid.set_seed(Some(seed as usize)),
+        Some(value) => return Err(invalid(format!("seed is not a positive integer: {:?}", value)))
+    }
+
+    if let Err(e) = parse_mask(&mut grid, object.get("mask")) {
+        return Err(e);
+    }
+
+    if let Err(e) = parse_cells(&mut grid, object.get("cells")) {
+        return Err(e);
+    }
+
+    let links = match parse_links(&grid, object.get("links"), "links", strict) {
+        Ok(links) => links,
+        Err(e) => return Err(e)
+    };
+
+    let one_way: HashSet<Link> = match parse_links(&grid, object.get("one_way"), "one_way", strict) {
+        Ok(one_way) => one_way.into_iter().collect(),
+        Err(e) => return Err(e)
+    };
+
+    if strict {
+        if let Err(e) = check_links(&grid, &links, &one_way) {
+            return Err(e);
+        }
+    }
+
+    for (from, to) in links {
+        grid.link_pair(from.0, from.1, to.0, to.1);
+    }
+
+    if let Err(e) = parse_link_weights(&mut grid, object.get("link_weights")) {
+        return Err(e);
+    }
+
+    match (parse_opening(&grid, object.get("entrance")), parse_opening(&grid, object.get("exit"))) {
+        (Ok(entrance), Ok(exit)) => {
+            grid.set_entrance(entrance);
+            grid.set_exit(exit);
+        },
+        (Err(e), _) | (_, Err(e)) => return Err(e)
+    }
+
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::types::cell::BaseCell;
+    use super::super::super::types::rectangle::Wrap;
+
+    #[test]
+    fn test_round_trip() {
+        let mut grid: Grid<BaseCell> = Grid::with_wrap(4, 3, Wrap::Cylinder);
+        grid.generate_aldous_broder_seeded(7);
+        grid.place_corners();
+        grid.link_one_way_indices(0, 0, 1, 0);
+        grid.link_indices(0, 0, 3, 2);
+        grid.set_weight(2, 1, 5);
+        grid.set_link_weight(0, 0, 3, 2, 4);
+        grid.set_metadata(1, 2, "item", "key");
+        // Masking a carved cell takes its links along
+        grid.set_masked(2, 2, true);
+
+        let parsed: Grid<BaseCell> = parse(&format(&grid)[..]).unwrap();
+        assert_eq!(parsed.links, grid.links);
+        assert_eq!(parsed.shape(), grid.shape());
+        assert_eq!(parsed.entrance(), grid.entrance());
+        assert_eq!(parsed.exit(), grid.exit());
+        assert_eq!(parsed.algorithm(), Some("aldous-broder"));
+        assert_eq!(parsed.seed(), Some(7));
+        assert_eq!(parsed.weight(2, 1), 5);
+        assert_eq!(parsed.link_weights(), vec![(((0, 0), (3, 2)), 4)]);
+        assert_eq!(parsed.metadata(1, 2).unwrap()["item"], "key");
+        assert_eq!(parsed.mask(), vec![(2, 2)]);
+    }
+
+    #[test]
+    fn test_version_1() {
+        let json = r#"{"x": 2, "y": 1, "links": [[[0, 0], [1, 0]], [[1, 0], [0, 0]]], "entrance": null, "exit": null}"#;
+        let grid: Grid<BaseCell> = parse(json).unwrap();
+        assert!(grid.is_linked_indices(1, 0, 0, 0));
+        assert_eq!(grid.algorithm(), None);
+
+        assert!(parse::<BaseCell>(r#"{"version": 3, "x": 2, "y": 1}"#).is_err());
+        assert!(parse::<BaseCell>(r#"{"kind": "hex", "x": 2, "y": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_rejects_bad_mazes() {
+        let asymmetric = r#"{"x": 2, "y": 1, "links": [[[0, 0], [1, 0]]]}"#;
+        assert!(parse::<BaseCell>(asymmetric).is_err());
+
+        let one_way = r#"{"x": 2, "y": 1, "links": [[[0, 0], [1, 0]]], "one_way": [[[0, 0], [1, 0]]]}"#;
+        assert!(parse::<BaseCell>(one_way).is_ok());
+
+        let outside = r#"{"x": 2, "y": 1, "links": [[[1, 0], [2, 0]], [[2, 0], [1, 0]]]}"#;
+        assert_eq!(parse::<BaseCell>(outside).err(), Some(MazeError::OutOfBounds(2, 0)));
+
+        let masked = r#"{"x": 2, "y": 1, "links": [[[0, 0], [1, 0]], [[1, 0], [0, 0]]], "mask": [[1, 0]]}"#;
+        assert!(parse::<BaseCell>(masked).is_err());
+
+        assert!(parse::<BaseCell>(r#"{"x": 0, "y": 1, "links": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_rejects_huge_sizes() {
+        let huge = r#"{"x": 1000000000000000000, "y": 1, "links": []}"#;
+        assert!(match parse::<BaseCell>(huge) {
+            Err(MazeError::Input(_)) => true,
+            _ => false
+        });
+
+        let overflowing = r#"{"x": 4294967296, "y": 4294967296, "links": []}"#;
+        assert!(parse::<BaseCell>(overflowing).is_err());
+        assert!(parse::<BaseCell>(r#"{"x": 1e18, "y": 1, "links": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_solution() {
+        let mut grid: Grid<BaseCell> = Grid::new(3, 1);
+        grid.link_indices(0, 0, 1, 0);
+        grid.link_indices(1, 0, 2, 0);
+
+        let document: Value = serde_json::from_str(&format_with_solution(&grid)[..]).unwrap();
+        assert_eq!(document.as_object().unwrap()["solution"].as_array().unwrap().len(), 3);
+    }
+}
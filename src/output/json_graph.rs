@@ -0,0 +1,52 @@
+extern crate serde_json;
+
+use serde_json::Map;
+
+use super::super::types::graph::Graph;
+use super::super::types::maze::Maze;
+use super::super::types::topology::{Linkable, Topology};
+
+fn format_pair(graph: &Graph, pair: (usize, usize)) -> serde_json::value::Value {
+    let mut tuple: Vec<serde_json::value::Value> = Vec::new();
+    tuple.push(serde_json::value::Value::U64(graph.id_of(pair.0)));
+    tuple.push(serde_json::value::Value::U64(graph.id_of(pair.1)));
+    serde_json::value::Value::Array(tuple)
+}
+
+/// Same document as accepted by `Graph::from_json`, plus the `links` which
+/// were carved, each listed once.
+pub fn format(maze: &Maze<Graph>) -> String {
+    let graph = maze.shape();
+    let mut map: Map<String, serde_json::Value> = Map::new();
+
+    let mut nodes: Vec<serde_json::value::Value> = Vec::new();
+    for index in 0..graph.size() {
+        let mut node: Map<String, serde_json::Value> = Map::new();
+        node.insert("id".to_string(), serde_json::value::Value::U64(graph.id_of(index)));
+        if let Some((x, y)) = graph.position(index) {
+            node.insert("x".to_string(), serde_json::value::Value::F64(x as f64));
+            node.insert("y".to_string(), serde_json::value::Value::F64(y as f64));
+        }
+        if graph.is_boundary_id(index) {
+            node.insert("boundary".to_string(), serde_json::value::Value::Bool(true));
+        }
+        nodes.push(serde_json::value::Value::Object(node));
+    }
+
+    let edges = graph.edges();
+    let links = edges.iter().filter(|&&(a, b)| maze.is_linked_ids(a, b)).map(|&pair| format_pair(graph, pair)).collect();
+    let edges = edges.iter().map(|&pair| format_pair(graph, pair)).collect();
+
+    map.insert("nodes".to_string(), serde_json::value::Value::Array(nodes));
+    map.insert("edges".to_string(), serde_json::value::Value::Array(edges));
+    map.insert("links".to_string(), serde_json::value::Value::Array(links));
+
+    match serde_json::to_string(&map) {
+        Ok(json) => {
+            return json;
+        },
+        Err(_) => {
+            return String::new()
+        }
+    }
+}
@@ -1,3 +1,15 @@
 pub mod ascii;
+pub mod ascii_levels;
+pub mod draw;
 pub mod json;
+pub mod json_graph;
 pub mod png;
+pub mod png_cube;
+pub mod png_delta;
+pub mod png_graph;
+pub mod png_hex;
+pub mod png_levels;
+pub mod png_polar;
+pub mod png_upsilon;
+pub mod png_voronoi;
+pub mod svg_graph;
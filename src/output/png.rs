@@ -1,19 +1,78 @@
 extern crate image;
 extern crate imageproc;
 
-use image::{Rgb, RgbImage};
+use image::{GenericImage, Rgb, RgbImage};
 use imageproc::rect::Rect;
 use imageproc::drawing::draw_filled_rect_mut;
 
 // use rand;
 // use rand::distributions::{Sample, Range};
 
+use super::super::error::MazeError;
 use super::super::types::cell::Cell;
 use super::super::types::grid::Grid;
+use super::draw::{draw_arrow, new_image, save};
+use super::super::types::opening::Opening;
+use super::super::types::rectangle::{Direction, Wrap};
+
+/// Colors of portal markers, both ends of a portal get the same one. They
+/// repeat when there are more portals.
+const PORTAL_COLORS: [[u8; 3]; 10] = [
+    [230, 25, 75],
+    [60, 180, 75],
+    [0, 130, 200],
+    [245, 130, 48],
+    [145, 30, 180],
+    [70, 240, 240],
+    [240, 50, 230],
+    [210, 245, 60],
+    [0, 128, 128],
+    [170, 110, 40]
+];
 
 pub fn format<T>(grid: &Grid<T>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str)
     where T: Cell + Clone
 {
+    try_format(grid, cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+}
+
+/// Same as `format`, with every cell tinted from the cell color towards the
+/// wall color by its weight, the heaviest cells the darkest.
+pub fn format_weights<T>(grid: &Grid<T>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str)
+    where T: Cell + Clone
+{
+    try_format_weights(grid, cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+}
+
+/// Same as `format`, failing on empty grids or when the image can't be
+/// written.
+pub fn try_format<T>(grid: &Grid<T>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError>
+    where T: Cell + Clone
+{
+    render(grid, cell_size, wall_size, color_cell, color_wall, false, output_filename)
+}
+
+/// Same as `format_weights`, failing on empty grids or when the image can't
+/// be written.
+pub fn try_format_weights<T>(grid: &Grid<T>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError>
+    where T: Cell + Clone
+{
+    render(grid, cell_size, wall_size, color_cell, color_wall, true, output_filename)
+}
+
+/// Color between the cell and wall colors, `t` being 0 for the cell color.
+fn mix(color_cell: &[u8; 3], color_wall: &[u8; 3], t: f32) -> Rgb<u8> {
+    let channel = |i: usize| (color_cell[i] as f32 + (color_wall[i] as f32 - color_cell[i] as f32) * t).round() as u8;
+    Rgb([channel(0), channel(1), channel(2)])
+}
+
+fn render<T>(grid: &Grid<T>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], weights: bool, output_filename: &'static str) -> Result<(), MazeError>
+    where T: Cell + Clone
+{
+    if grid.size() == 0 {
+        return Err(MazeError::EmptyGrid(grid.x(), grid.y()));
+    }
+
     let img_x = (grid.x() as u32 * cell_size) + (grid.x() as u32 + 1) * wall_size;
     let img_y = (grid.y() as u32 * cell_size) + (grid.y() as u32 + 1) * wall_size;
 
@@ -34,6 +93,22 @@ pub fn format<T>(grid: &Grid<T>, cell_size: u32, wall_size: u32, color_cell: &[u
     // let mut between = Range::new(0, 255);
     // let mut rng = rand::thread_rng();
 
+    // Weights, at most 60% of the way to the wall color to keep walls visible
+    let max_weight = grid.max_weight();
+    if weights && max_weight > 1 {
+        for x in 0..grid.x() {
+            for y in 0..grid.y() {
+                let weight = grid.weight(x, y);
+                if weight > 1 {
+                    let t = 0.6 * (weight - 1) as f32 / (max_weight - 1) as f32;
+                    let start_x = x as i32 * cell_size as i32 + (x + 1) as i32 * wall_size as i32;
+                    let start_y = y as i32 * cell_size as i32 + (y + 1) as i32 * wall_size as i32;
+                    draw_filled_rect_mut(&mut img, Rect::at(start_x, start_y).of_size(cell_size, cell_size), mix(color_cell, color_wall, t));
+                }
+            }
+        }
+    }
+
     // Cells
     for x in 0..grid.x() {
         for y in 0..grid.y() {
@@ -53,7 +128,7 @@ pub fn format<T>(grid: &Grid<T>, cell_size: u32, wall_size: u32, color_cell: &[u
             */
 
             // Right - Vertical
-            let right = grid.is_linked_indices(cell.x(), cell.y(), cell.x() + 1, cell.y());
+            let right = grid.is_open((cell.x(), cell.y()), Direction::East);
             if !right {
                 let start_x = (x + 1) as i32 * cell_size as i32 + (x + 1) as i32 * wall_size as i32;
                 let start_y = y as i32 * cell_size as i32 + y as i32 * wall_size as i32;
@@ -64,7 +139,7 @@ pub fn format<T>(grid: &Grid<T>, cell_size: u32, wall_size: u32, color_cell: &[u
             }
 
             // Bottom - Horizontal
-            let bottom = grid.is_linked_indices(cell.x(), cell.y(), cell.x(), cell.y() + 1);
+            let bottom = grid.is_open((cell.x(), cell.y()), Direction::South);
             if !bottom {
                 let start_x = x as i32 * cell_size as i32 + x as i32 * wall_size as i32;
                 let start_y = (y + 1) as i32 * cell_size as i32 + (y + 1) as i32 * wall_size as i32;
@@ -76,5 +151,120 @@ pub fn format<T>(grid: &Grid<T>, cell_size: u32, wall_size: u32, color_cell: &[u
         }
     }
 
-    img.save(output_filename).unwrap();
+    // Passages wrapping around the top and left edges
+    let mut openings: Vec<Opening> = Vec::new();
+    for x in 0..grid.x() {
+        if grid.is_open((x, 0), Direction::North) {
+            openings.push(Opening::new(x, 0, Direction::North));
+        }
+    }
+
+    for y in 0..grid.y() {
+        if grid.is_open((0, y), Direction::West) {
+            openings.push(Opening::new(0, y, Direction::West));
+        }
+    }
+
+    // Entrance & Exit
+    openings.extend(grid.entrance().iter().chain(grid.exit().iter()));
+    for opening in openings.iter() {
+        let (start_x, start_y, size_x, size_y) = opening_rect(opening, cell_size, wall_size);
+        debug!("opening: ({}, {}), start: ({}, {}), size({}, {})", opening.x, opening.y, start_x, start_y, size_x, size_y);
+        draw_filled_rect_mut(&mut img, Rect::at(start_x, start_y).of_size(size_x, size_y), background_color);
+    }
+
+    // Portals, a square of the same color in both cells
+    let marker = (cell_size / 2).max(1);
+    let offset = wall_size as i32 + (cell_size - marker) as i32 / 2;
+    for (i, &(from, to)) in grid.portals().iter().enumerate() {
+        let color = Rgb(PORTAL_COLORS[i % PORTAL_COLORS.len()]);
+        for &(x, y) in [from, to].iter() {
+            let start_x = offset + x as i32 * (cell_size + wall_size) as i32;
+            let start_y = offset + y as i32 * (cell_size + wall_size) as i32;
+            draw_filled_rect_mut(&mut img, Rect::at(start_x, start_y).of_size(marker, marker), color);
+        }
+    }
+
+    // One-way passages, arrows between the centers of neighbouring cells
+    let step = (cell_size + wall_size) as f32;
+    let center = |x: usize, y: usize| (wall_size as f32 + cell_size as f32 / 2.0 + x as f32 * step, wall_size as f32 + cell_size as f32 / 2.0 + y as f32 * step);
+    let arrow_size = cell_size as f32 / 5.0;
+    let arrow_thickness = (wall_size / 3).max(1);
+    for x in 0..grid.x() {
+        for y in 0..grid.y() {
+            for &direction in [Direction::East, Direction::South].iter() {
+                match grid.neighbor((x, y), direction) {
+                    // Passages across a joined edge have no arrow
+                    Some((nx, ny)) if nx >= x && ny >= y => {
+                        if grid.is_one_way_indices(x, y, nx, ny) {
+                            draw_arrow(&mut img, center(x, y), center(nx, ny), 1, arrow_size, arrow_thickness, wall_color);
+                        } else if grid.is_one_way_indices(nx, ny, x, y) {
+                            draw_arrow(&mut img, center(nx, ny), center(x, y), 1, arrow_size, arrow_thickness, wall_color);
+                        }
+                    },
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let img = match grid.shape().wrap() {
+        Wrap::None => img,
+        wrap => mark_seams(&img, wrap, cell_size, wall_size, color_cell, color_wall)
+    };
+
+    save(&img, output_filename)
+}
+
+/// Surrounds the maze with arrows marking which edges are joined, as in
+/// textbook diagrams. West and east edges get single arrows, pointing the
+/// opposite way where they are joined upside down, north and south edges
+/// get double arrows.
+fn mark_seams(maze: &RgbImage, wrap: Wrap, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3]) -> RgbImage {
+    let margin = cell_size;
+    let (maze_x, maze_y) = maze.dimensions();
+
+    let mut img = new_image(maze_x + 2 * margin, maze_y + 2 * margin, color_cell);
+    img.copy_from(maze, margin, margin);
+
+    let wall_color = Rgb(*color_wall);
+    let thickness = (wall_size / 2).max(1);
+    let size = cell_size as f32 / 4.0;
+
+    let near = margin as f32 / 2.0;
+    let far_x = (margin + maze_x) as f32 + near;
+    let far_y = (margin + maze_y) as f32 + near;
+    let top = margin as f32;
+    let bottom = (margin + maze_y) as f32;
+    let left = margin as f32;
+    let right = (margin + maze_x) as f32;
+
+    if wrap.wraps_x() {
+        draw_arrow(&mut img, (near, top), (near, bottom), 1, size, thickness, wall_color);
+        match wrap.flips_x() {
+            true => draw_arrow(&mut img, (far_x, bottom), (far_x, top), 1, size, thickness, wall_color),
+            false => draw_arrow(&mut img, (far_x, top), (far_x, bottom), 1, size, thickness, wall_color)
+        }
+    }
+
+    if wrap.wraps_y() {
+        draw_arrow(&mut img, (left, near), (right, near), 2, size, thickness, wall_color);
+        draw_arrow(&mut img, (left, far_y), (right, far_y), 2, size, thickness, wall_color);
+    }
+
+    img
+}
+
+/// Part of the outer wall removed by an opening, as (start_x, start_y, size_x, size_y).
+fn opening_rect(opening: &Opening, cell_size: u32, wall_size: u32) -> (i32, i32, u32, u32) {
+    let step = (cell_size + wall_size) as i32;
+    let x = opening.x as i32;
+    let y = opening.y as i32;
+
+    match opening.side {
+        Direction::North => (x * step + wall_size as i32, 0, cell_size, wall_size),
+        Direction::South => (x * step + wall_size as i32, (y + 1) * step, cell_size, wall_size),
+        Direction::West => (0, y * step + wall_size as i32, wall_size, cell_size),
+        Direction::East => ((x + 1) * step, y * step + wall_size as i32, wall_size, cell_size)
+    }
 }
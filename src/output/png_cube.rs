@@ -0,0 +1,62 @@
+extern crate image;
+
+use image::Rgb;
+
+use super::draw::{draw_wall, new_image, save};
+use super::super::error::MazeError;
+use super::super::types::cube::{Cube, CubeDirection, CUBE_DIRECTIONS};
+use super::super::types::maze::Maze;
+use super::super::types::topology::Linkable;
+
+/// Unfolded net of the cube. Passages crossing an edge which is not shared
+/// in the net show up as gaps in the outline of both faces.
+pub fn format(maze: &Maze<Cube>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+    try_format(maze, cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+}
+
+/// Same as `format`, failing when the image can't be written.
+pub fn try_format(maze: &Maze<Cube>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+    let cube = maze.shape();
+    let n = cube.n();
+    let cell = cell_size as f32;
+    let margin = wall_size as f32;
+
+    let img_x = (4.0 * n as f32 * cell + 2.0 * margin).ceil() as u32;
+    let img_y = (3.0 * n as f32 * cell + 2.0 * margin).ceil() as u32;
+
+    info!("Generating {:?}, size: {}x{} px", output_filename, img_x, img_y);
+    let mut img = new_image(img_x, img_y, color_cell);
+    let wall_color = Rgb(*color_wall);
+
+    for face in 0..6 {
+        let (net_x, net_y) = cube.net(face);
+
+        for x in 0..n {
+            for y in 0..n {
+                let id = cube.id(face, x, y);
+                let left = margin + (net_x * n + x) as f32 * cell;
+                let top = margin + (net_y * n + y) as f32 * cell;
+                let right = left + cell;
+                let bottom = top + cell;
+
+                for &direction in CUBE_DIRECTIONS.iter() {
+                    let (nf, nx, ny) = cube.neighbor(face, x, y, direction);
+                    if maze.is_linked_ids(id, cube.id(nf, nx, ny)) {
+                        continue;
+                    }
+
+                    let (start, end) = match direction {
+                        CubeDirection::North => ((left, top), (right, top)),
+                        CubeDirection::East => ((right, top), (right, bottom)),
+                        CubeDirection::South => ((left, bottom), (right, bottom)),
+                        CubeDirection::West => ((left, top), (left, bottom))
+                    };
+
+                    draw_wall(&mut img, start, end, wall_size, wall_color);
+                }
+            }
+        }
+    }
+
+    save(&img, output_filename)
+}
@@ -0,0 +1,67 @@
+extern crate image;
+
+use image::Rgb;
+
+use super::draw::{draw_wall, new_image, save};
+use super::super::error::MazeError;
+use super::super::types::delta::{Delta, DeltaDirection};
+use super::super::types::maze::Maze;
+use super::super::types::topology::Linkable;
+
+pub fn format(maze: &Maze<Delta>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+    try_format(maze, cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+}
+
+/// Same as `format`, failing when the image can't be written.
+pub fn try_format(maze: &Maze<Delta>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+    let delta = maze.shape();
+    let width = cell_size as f32;
+    let height = width * 3f32.sqrt() / 2.0;
+    let margin = wall_size as f32;
+
+    let img_x = (width / 2.0 * (delta.x() as f32 + 1.0) + margin * 2.0).ceil() as u32;
+    let img_y = (height * delta.y() as f32 + margin * 2.0).ceil() as u32;
+
+    info!("Generating {:?}, size: {}x{} px", output_filename, img_x, img_y);
+    let mut img = new_image(img_x, img_y, color_cell);
+    let wall_color = Rgb(*color_wall);
+
+    for x in 0..delta.x() {
+        for y in 0..delta.y() {
+            let id = delta.id(x, y);
+
+            let center_x = margin + width / 2.0 + x as f32 * width / 2.0;
+            let west_x = center_x - width / 2.0;
+            let east_x = center_x + width / 2.0;
+            let top_y = margin + y as f32 * height;
+            let bottom_y = top_y + height;
+
+            // Apex and base of the triangle
+            let (apex_y, base_y) = match delta.is_upright(x, y) {
+                true => (top_y, bottom_y),
+                false => (bottom_y, top_y)
+            };
+
+            for &direction in delta.directions(x, y).iter() {
+                let open = match delta.neighbor(x, y, direction) {
+                    Some((nx, ny)) => maze.is_linked_ids(id, delta.id(nx, ny)),
+                    None => false
+                };
+
+                if open {
+                    continue;
+                }
+
+                let (start, end) = match direction {
+                    DeltaDirection::West => ((west_x, base_y), (center_x, apex_y)),
+                    DeltaDirection::East => ((east_x, base_y), (center_x, apex_y)),
+                    DeltaDirection::North | DeltaDirection::South => ((west_x, base_y), (east_x, base_y))
+                };
+
+                draw_wall(&mut img, start, end, wall_size, wall_color);
+            }
+        }
+    }
+
+    save(&img, output_filename)
+}
@@ -0,0 +1,63 @@
+extern crate image;
+
+use image::Rgb;
+
+use super::draw::{draw_wall, new_image, save};
+use super::super::error::MazeError;
+use super::super::types::graph::Graph;
+use super::super::types::maze::Maze;
+use super::super::types::topology::{Linkable, Topology};
+
+/// Pixel positions of the nodes, with `(0, 0)` at the smallest coordinates,
+/// and the size of the image, which has a margin around the outermost
+/// nodes. Fails on graphs with nodes without a position.
+pub fn layout(graph: &Graph, cell_size: u32, wall_size: u32) -> Result<(Vec<(f32, f32)>, u32, u32), MazeError> {
+    if !graph.has_positions() {
+        return Err(MazeError::Output("graph needs positions of all nodes to be drawn".to_string()));
+    }
+
+    let (min, max) = graph.bounds();
+    let corridor = cell_size.saturating_sub(wall_size).max(1);
+    let margin = wall_size as f32 + corridor as f32 / 2.0;
+
+    let pixels = (0..graph.size()).filter_map(|index| graph.position(index)).map(|position| {
+        (margin + (position.0 - min.0) * cell_size as f32, margin + (position.1 - min.1) * cell_size as f32)
+    }).collect();
+
+    let img_x = ((max.0 - min.0) * cell_size as f32 + 2.0 * margin).ceil() as u32;
+    let img_y = ((max.1 - min.1) * cell_size as f32 + 2.0 * margin).ceil() as u32;
+    Ok((pixels, img_x, img_y))
+}
+
+/// Carves corridors `cell_size - wall_size` wide out of a solid image, so a
+/// graph laid out on a unit grid looks like a rectangular maze.
+pub fn format(maze: &Maze<Graph>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+    try_format(maze, cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+}
+
+/// Same as `format`, failing on nodes without a position or when the image
+/// can't be written.
+pub fn try_format(maze: &Maze<Graph>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+    let graph = maze.shape();
+    let (pixels, img_x, img_y) = match layout(graph, cell_size, wall_size) {
+        Ok(layout) => layout,
+        Err(e) => return Err(e)
+    };
+    let corridor = cell_size.saturating_sub(wall_size).max(1);
+
+    info!("Generating {:?}, size: {}x{} px", output_filename, img_x, img_y);
+    let mut img = new_image(img_x, img_y, color_wall);
+    let cell_color = Rgb(*color_cell);
+
+    for &center in pixels.iter() {
+        draw_wall(&mut img, center, center, corridor, cell_color);
+    }
+
+    for (a, b) in graph.edges() {
+        if maze.is_linked_ids(a, b) {
+            draw_wall(&mut img, pixels[a], pixels[b], corridor, cell_color);
+        }
+    }
+
+    save(&img, output_filename)
+}
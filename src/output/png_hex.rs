@@ -0,0 +1,72 @@
+extern crate image;
+
+use image::Rgb;
+
+use super::draw::{draw_wall, new_image, save};
+use super::super::error::MazeError;
+use super::super::types::hex::{Hex, HexDirection, HEX_DIRECTIONS};
+use super::super::types::maze::Maze;
+use super::super::types::topology::Linkable;
+
+/// Corners of the edge shared with the neighbour in the given direction.
+fn edge(center: (f32, f32), radius: f32, direction: HexDirection) -> ((f32, f32), (f32, f32)) {
+    let (cx, cy) = center;
+    let half_height = radius * 3f32.sqrt() / 2.0;
+
+    let east = (cx + radius, cy);
+    let west = (cx - radius, cy);
+    let north_east = (cx + radius / 2.0, cy - half_height);
+    let north_west = (cx - radius / 2.0, cy - half_height);
+    let south_east = (cx + radius / 2.0, cy + half_height);
+    let south_west = (cx - radius / 2.0, cy + half_height);
+
+    match direction {
+        HexDirection::North => (north_west, north_east),
+        HexDirection::NorthEast => (north_east, east),
+        HexDirection::SouthEast => (east, south_east),
+        HexDirection::South => (south_east, south_west),
+        HexDirection::SouthWest => (south_west, west),
+        HexDirection::NorthWest => (west, north_west)
+    }
+}
+
+pub fn format(maze: &Maze<Hex>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+    try_format(maze, cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+}
+
+/// Same as `format`, failing when the image can't be written.
+pub fn try_format(maze: &Maze<Hex>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+    let hex = maze.shape();
+    let radius = cell_size as f32 / 2.0;
+    let half_height = radius * 3f32.sqrt() / 2.0;
+    let margin = wall_size as f32;
+
+    let img_x = (radius * 1.5 * (hex.x() as f32 - 1.0) + radius * 2.0 + margin * 2.0).ceil() as u32;
+    let img_y = (half_height * 2.0 * hex.y() as f32 + half_height + margin * 2.0).ceil() as u32;
+
+    info!("Generating {:?}, size: {}x{} px", output_filename, img_x, img_y);
+    let mut img = new_image(img_x, img_y, color_cell);
+    let wall_color = Rgb(*color_wall);
+
+    for x in 0..hex.x() {
+        for y in 0..hex.y() {
+            let id = hex.id(x, y);
+            let cx = margin + radius + radius * 1.5 * x as f32;
+            let cy = margin + half_height + half_height * 2.0 * y as f32 + (x % 2) as f32 * half_height;
+
+            for &direction in HEX_DIRECTIONS.iter() {
+                let open = match hex.neighbor(x, y, direction) {
+                    Some((nx, ny)) => maze.is_linked_ids(id, hex.id(nx, ny)),
+                    None => false
+                };
+
+                if !open {
+                    let (start, end) = edge((cx, cy), radius, direction);
+                    draw_wall(&mut img, start, end, wall_size, wall_color);
+                }
+            }
+        }
+    }
+
+    save(&img, output_filename)
+}
@@ -0,0 +1,93 @@
+extern crate image;
+extern crate imageproc;
+
+use image::{Rgb, RgbImage};
+use imageproc::rect::Rect;
+use imageproc::drawing::draw_filled_rect_mut;
+
+use super::draw::{draw_wall, new_image, save};
+use super::super::error::MazeError;
+use super::super::types::levels::{LevelDirection, Levels};
+use super::super::types::maze::Maze;
+
+/// Chevron pointing up (`direction` -1) or down (`direction` 1).
+fn draw_chevron(img: &mut RgbImage, center: (f32, f32), size: f32, direction: f32, thickness: u32, color: Rgb<u8>) {
+    let tip = (center.0, center.1 + direction * size / 2.0);
+    draw_wall(img, (center.0 - size, center.1 - direction * size / 2.0), tip, thickness, color);
+    draw_wall(img, tip, (center.0 + size, center.1 - direction * size / 2.0), thickness, color);
+}
+
+/// Levels side by side, one cell apart; chevrons mark stairs up and down.
+pub fn format(maze: &Maze<Levels>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+    try_format(maze, cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+}
+
+/// Same as `format`, failing when the image can't be written.
+pub fn try_format(maze: &Maze<Levels>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+    let levels = maze.shape();
+    let level_x = (levels.x() as u32 * cell_size) + (levels.x() as u32 + 1) * wall_size;
+    let level_y = (levels.y() as u32 * cell_size) + (levels.y() as u32 + 1) * wall_size;
+
+    let img_x = levels.z() as u32 * level_x + (levels.z() as u32 - 1) * cell_size;
+    let img_y = level_y;
+
+    info!("Generating {:?}, size: {}x{} px", output_filename, img_x, img_y);
+    let mut img = new_image(img_x, img_y, color_cell);
+    let wall_color = Rgb(*color_wall);
+    let marker_size = cell_size as f32 / 4.0;
+    let marker_thickness = (wall_size / 2).max(1);
+
+    for z in 0..levels.z() {
+        let offset = (z as u32 * (level_x + cell_size)) as i32;
+
+        // Top
+        draw_filled_rect_mut(&mut img, Rect::at(offset, 0).of_size(level_x, wall_size), wall_color);
+
+        // Left
+        draw_filled_rect_mut(&mut img, Rect::at(offset, 0).of_size(wall_size, level_y), wall_color);
+
+        for x in 0..levels.x() {
+            for y in 0..levels.y() {
+                let start_x = offset + x as i32 * (cell_size + wall_size) as i32;
+                let start_y = y as i32 * (cell_size + wall_size) as i32;
+
+                // Right - Vertical
+                if !maze.is_open(x, y, z, LevelDirection::East) {
+                    let rect = Rect::at(start_x + (cell_size + wall_size) as i32, start_y).of_size(wall_size, cell_size + 2 * wall_size);
+                    draw_filled_rect_mut(&mut img, rect, wall_color);
+                }
+
+                // Bottom - Horizontal
+                if !maze.is_open(x, y, z, LevelDirection::South) {
+                    let rect = Rect::at(start_x, start_y + (cell_size + wall_size) as i32).of_size(cell_size + 2 * wall_size, wall_size);
+                    draw_filled_rect_mut(&mut img, rect, wall_color);
+                }
+
+                // Stairs
+                let center = (
+                    start_x as f32 + wall_size as f32 + cell_size as f32 / 2.0,
+                    start_y as f32 + wall_size as f32 + cell_size as f32 / 2.0
+                );
+
+                let up = maze.is_open(x, y, z, LevelDirection::Up);
+                let down = maze.is_open(x, y, z, LevelDirection::Down);
+
+                // Both chevrons at once are drawn smaller, next to each other
+                let (size, shift) = match up && down {
+                    true => (marker_size * 0.6, marker_size),
+                    false => (marker_size, 0.0)
+                };
+
+                if up {
+                    draw_chevron(&mut img, (center.0 - shift, center.1), size, -1.0, marker_thickness, wall_color);
+                }
+
+                if down {
+                    draw_chevron(&mut img, (center.0 + shift, center.1), size, 1.0, marker_thickness, wall_color);
+                }
+            }
+        }
+    }
+
+    save(&img, output_filename)
+}
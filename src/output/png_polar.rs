@@ -0,0 +1,79 @@
+extern crate image;
+
+use std::f32::consts::PI;
+
+use image::{Rgb, RgbImage};
+
+use super::draw::{draw_wall, new_image, save};
+use super::super::error::MazeError;
+use super::super::types::maze::Maze;
+use super::super::types::polar::Polar;
+use super::super::types::topology::Linkable;
+
+fn point(center: f32, radius: f32, theta: f32) -> (f32, f32) {
+    (center + radius * theta.cos(), center + radius * theta.sin())
+}
+
+/// Approximates the arc by straight segments about `wall_size` pixels long.
+fn draw_arc(img: &mut RgbImage, center: f32, radius: f32, theta_start: f32, theta_end: f32, wall_size: u32, color: Rgb<u8>) {
+    let length = radius * (theta_end - theta_start);
+    let segments = (length / wall_size.max(4) as f32).ceil().max(1.0) as u32;
+
+    let mut previous = point(center, radius, theta_start);
+    for i in 1..(segments + 1) {
+        let theta = theta_start + (theta_end - theta_start) * i as f32 / segments as f32;
+        let current = point(center, radius, theta);
+        draw_wall(img, previous, current, wall_size, color);
+        previous = current;
+    }
+}
+
+pub fn format(maze: &Maze<Polar>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+    try_format(maze, cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+}
+
+/// Same as `format`, failing when the image can't be written.
+pub fn try_format(maze: &Maze<Polar>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+    let polar = maze.shape();
+    let ring_height = cell_size as f32;
+    let margin = wall_size as f32;
+
+    let img_size = (2.0 * ring_height * polar.rings() as f32 + 2.0 * margin).ceil() as u32;
+    let center = img_size as f32 / 2.0;
+
+    info!("Generating {:?}, size: {}x{} px", output_filename, img_size, img_size);
+    let mut img = new_image(img_size, img_size, color_cell);
+    let wall_color = Rgb(*color_wall);
+
+    for ring in 1..polar.rings() {
+        let count = polar.count(ring);
+        let theta = 2.0 * PI / count as f32;
+        let inner_radius = ring as f32 * ring_height;
+        let outer_radius = (ring + 1) as f32 * ring_height;
+
+        for index in 0..count {
+            let id = polar.id(ring, index);
+            let theta_ccw = index as f32 * theta;
+            let theta_cw = (index + 1) as f32 * theta;
+
+            // Inward - Arc
+            let inward = polar.inward(ring, index).unwrap();
+            if !maze.is_linked_ids(id, polar.id(inward.0, inward.1)) {
+                draw_arc(&mut img, center, inner_radius, theta_ccw, theta_cw, wall_size, wall_color);
+            }
+
+            // Clockwise - Radial
+            let clockwise = polar.clockwise(ring, index).unwrap();
+            if !maze.is_linked_ids(id, polar.id(clockwise.0, clockwise.1)) {
+                let start = point(center, inner_radius, theta_cw);
+                let end = point(center, outer_radius, theta_cw);
+                draw_wall(&mut img, start, end, wall_size, wall_color);
+            }
+        }
+    }
+
+    // Outer boundary
+    draw_arc(&mut img, center, polar.rings() as f32 * ring_height, 0.0, 2.0 * PI, wall_size, wall_color);
+
+    save(&img, output_filename)
+}
@@ -0,0 +1,74 @@
+extern crate image;
+
+use image::Rgb;
+
+use super::draw::{draw_wall, new_image, save};
+use super::super::error::MazeError;
+use super::super::types::maze::Maze;
+use super::super::types::topology::Linkable;
+use super::super::types::upsilon::{Upsilon, UpsilonDirection};
+
+/// Endpoints of the edge facing the given direction, relative to the cell
+/// center. `half` is half the width of the cell, `side` half the side of a
+/// square (which is also the half-length of the straight octagon edges).
+fn edge(half: f32, side: f32, direction: UpsilonDirection) -> ((f32, f32), (f32, f32)) {
+    match direction {
+        UpsilonDirection::North => ((-side, -half), (side, -half)),
+        UpsilonDirection::NorthEast => ((side, -half), (half, -side)),
+        UpsilonDirection::East => ((half, -side), (half, side)),
+        UpsilonDirection::SouthEast => ((half, side), (side, half)),
+        UpsilonDirection::South => ((side, half), (-side, half)),
+        UpsilonDirection::SouthWest => ((-side, half), (-half, side)),
+        UpsilonDirection::West => ((-half, side), (-half, -side)),
+        UpsilonDirection::NorthWest => ((-half, -side), (-side, -half))
+    }
+}
+
+pub fn format(maze: &Maze<Upsilon>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+    try_format(maze, cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+}
+
+/// Same as `format`, failing when the image can't be written.
+pub fn try_format(maze: &Maze<Upsilon>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+    let upsilon = maze.shape();
+
+    // Distance between neighbouring cell centers, chosen so that octagons
+    // are regular: pitch = square + square / sqrt(2)
+    let pitch = cell_size as f32;
+    let side = pitch / (1.0 + 1.0 / 2f32.sqrt()) / 2.0;
+    let octagon_half = pitch - side;
+    let margin = wall_size as f32;
+
+    let img_x = (2.0 * octagon_half + pitch * (upsilon.x() as f32 - 1.0) + 2.0 * margin).ceil() as u32;
+    let img_y = (2.0 * octagon_half + pitch * (upsilon.y() as f32 - 1.0) + 2.0 * margin).ceil() as u32;
+
+    info!("Generating {:?}, size: {}x{} px", output_filename, img_x, img_y);
+    let mut img = new_image(img_x, img_y, color_cell);
+    let wall_color = Rgb(*color_wall);
+
+    for x in 0..upsilon.x() {
+        for y in 0..upsilon.y() {
+            let id = upsilon.id(x, y);
+            let cx = margin + octagon_half + x as f32 * pitch;
+            let cy = margin + octagon_half + y as f32 * pitch;
+            let half = match upsilon.is_octagon(x, y) {
+                true => octagon_half,
+                false => side
+            };
+
+            for &direction in upsilon.directions(x, y).iter() {
+                let open = match upsilon.neighbor(x, y, direction) {
+                    Some((nx, ny)) => maze.is_linked_ids(id, upsilon.id(nx, ny)),
+                    None => false
+                };
+
+                if !open {
+                    let (start, end) = edge(half, side, direction);
+                    draw_wall(&mut img, (cx + start.0, cy + start.1), (cx + end.0, cy + end.1), wall_size, wall_color);
+                }
+            }
+        }
+    }
+
+    save(&img, output_filename)
+}
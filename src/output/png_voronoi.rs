@@ -0,0 +1,46 @@
+extern crate image;
+
+use image::Rgb;
+
+use super::draw::{draw_wall, new_image, save};
+use super::super::error::MazeError;
+use super::super::types::maze::Maze;
+use super::super::types::topology::{Linkable, Topology};
+use super::super::types::voronoi::Voronoi;
+
+pub fn format(maze: &Maze<Voronoi>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+    try_format(maze, cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+}
+
+/// Same as `format`, failing when the image can't be written.
+pub fn try_format(maze: &Maze<Voronoi>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+    let voronoi = maze.shape();
+    let scale = cell_size as f32;
+    let margin = wall_size as f32;
+
+    let img_x = (voronoi.width() * scale + 2.0 * margin).ceil() as u32;
+    let img_y = (voronoi.height() * scale + 2.0 * margin).ceil() as u32;
+
+    info!("Generating {:?}, size: {}x{} px", output_filename, img_x, img_y);
+    let mut img = new_image(img_x, img_y, color_cell);
+    let wall_color = Rgb(*color_wall);
+
+    for id in 0..voronoi.size() {
+        let polygon = voronoi.polygon(id);
+        for (i, &across) in voronoi.across(id).iter().enumerate() {
+            // Walls between two cells are drawn from the lower id only
+            let wall = match across {
+                Some(other) => id < other && !maze.is_linked_ids(id, other),
+                None => true
+            };
+
+            if wall {
+                let start = polygon[i];
+                let end = polygon[(i + 1) % polygon.len()];
+                draw_wall(&mut img, (margin + start.0 * scale, margin + start.1 * scale), (margin + end.0 * scale, margin + end.1 * scale), wall_size, wall_color);
+            }
+        }
+    }
+
+    save(&img, output_filename)
+}
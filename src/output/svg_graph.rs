@@ -0,0 +1,46 @@
+use super::png_graph::layout;
+use super::super::error::MazeError;
+use super::super::types::graph::Graph;
+use super::super::types::maze::Maze;
+use super::super::types::topology::Linkable;
+
+fn color(rgb: &[u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+/// Same drawing as `output::png_graph`, as an SVG document.
+pub fn format(maze: &Maze<Graph>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3]) -> String {
+    try_format(maze, cell_size, wall_size, color_cell, color_wall).unwrap()
+}
+
+/// Same as `format`, failing on nodes without a position.
+pub fn try_format(maze: &Maze<Graph>, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3]) -> Result<String, MazeError> {
+    let graph = maze.shape();
+    let (pixels, img_x, img_y) = match layout(graph, cell_size, wall_size) {
+        Ok(layout) => layout,
+        Err(e) => return Err(e)
+    };
+    let corridor = cell_size.saturating_sub(wall_size).max(1) as f32;
+
+    let mut res = String::new();
+    res += &format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n", img_x, img_y)[..];
+    res += &format!("<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", img_x, img_y, color(color_wall))[..];
+
+    res += &format!("<g fill=\"{}\">\n", color(color_cell))[..];
+    for &(x, y) in pixels.iter() {
+        res += &format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>\n", x - corridor / 2.0, y - corridor / 2.0, corridor, corridor)[..];
+    }
+    res += "</g>\n";
+
+    res += &format!("<g stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"square\">\n", color(color_cell), corridor)[..];
+    for (a, b) in graph.edges() {
+        if maze.is_linked_ids(a, b) {
+            let (start, end) = (pixels[a], pixels[b]);
+            res += &format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n", start.0, start.1, end.0, end.1)[..];
+        }
+    }
+    res += "</g>\n";
+
+    res += "</svg>\n";
+    Ok(res)
+}
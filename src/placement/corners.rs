@@ -0,0 +1,25 @@
+use super::super::error::MazeError;
+use super::super::types::cell::Cell;
+use super::super::types::grid::Grid;
+use super::super::types::opening::Opening;
+
+/// Entrance in the top-left cell, exit in the bottom-right cell, through the
+/// west and east walls unless those wrap around. Fails on empty grids,
+/// which have no corners.
+pub fn place<T>(grid: &mut Grid<T>) -> Result<(), MazeError>
+    where T: Cell + Clone
+{
+    if grid.x() == 0 || grid.y() == 0 {
+        return Err(MazeError::EmptyGrid(grid.x(), grid.y()));
+    }
+
+    let last_x = grid.x() - 1;
+    let last_y = grid.y() - 1;
+
+    let entrance = grid.boundary_side(0, 0).map(|side| Opening::new(0, 0, side));
+    let exit = grid.boundary_side(last_x, last_y).map(|side| Opening::new(last_x, last_y, side));
+
+    grid.set_entrance(entrance);
+    grid.set_exit(exit);
+    Ok(())
+}
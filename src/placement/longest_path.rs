@@ -0,0 +1,121 @@
+use super::super::distance;
+use super::super::types::cell::Cell;
+use super::super::types::grid::Grid;
+use super::super::types::opening::Opening;
+use super::super::types::topology::Topology;
+
+/// Boundary cell farthest away from the root of `distances`, following
+/// links, and its distance.
+fn farthest_boundary(distances: &distance::dijkstra::Distances, start: usize, boundary: &[usize]) -> (usize, usize) {
+    let mut best = start;
+    let mut best_distance = 0;
+    for &id in boundary {
+        match distances.get(id) {
+            Some(d) if d > best_distance => {
+                best = id;
+                best_distance = d;
+            },
+            _ => {}
+        }
+    }
+
+    (best, best_distance)
+}
+
+/// Entrance and exit on the pair of boundary cells with the longest path
+/// between them, found by two breadth-first searches in every part of the
+/// maze with a boundary cell. Masked cells are left out. No openings are
+/// placed when no two distinct boundary cells are linked, such as in a maze
+/// not carved yet.
+pub fn place<T>(grid: &mut Grid<T>)
+    where T: Cell + Clone
+{
+    let boundary = grid.boundary_ids();
+
+    let mut best: Option<(usize, usize, usize)> = None;
+    let mut seen = vec![false; grid.size()];
+    for &start in boundary.iter() {
+        if seen[start] {
+            continue;
+        }
+
+        let distances = distance::dijkstra::distances(grid, start);
+        for id in 0..grid.size() {
+            seen[id] = seen[id] || distances.get(id).is_some();
+        }
+
+        let (first, _) = farthest_boundary(&distances, start, &boundary);
+        let (second, length) = farthest_boundary(&distance::dijkstra::distances(grid, first), first, &boundary);
+        if second != first && best.map_or(true, |(_, _, best_length)| length > best_length) {
+            best = Some((first, second, length));
+        }
+    }
+
+    let (first, second) = match best {
+        Some((first, second, _)) => (grid.shape().coords(first), grid.shape().coords(second)),
+        None => {
+            grid.set_entrance(None);
+            grid.set_exit(None);
+            return;
+        }
+    };
+
+    let entrance = Opening::new(first.0, first.1, grid.boundary_side(first.0, first.1).unwrap());
+    let exit = Opening::new(second.0, second.1, grid.boundary_side(second.0, second.1).unwrap());
+
+    grid.set_entrance(Some(entrance));
+    grid.set_exit(Some(exit));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::distance;
+    use super::super::super::types::cell::*;
+    use super::super::super::types::grid::Grid;
+
+    #[test]
+    fn test_place_longest_path_is_not_shorter_than_corners() {
+        let mut grid: Grid<BaseCell> = Grid::new(10, 10);
+        grid.generate_aldous_broder();
+        grid.place_longest_path();
+
+        let entrance = grid.entrance().unwrap();
+        let exit = grid.exit().unwrap();
+        assert!(grid.is_boundary_indices(entrance.x, entrance.y));
+        assert!(grid.is_boundary_indices(exit.x, exit.y));
+
+        let longest = distance::dijkstra::calculate(&grid, entrance.cell(), exit.cell());
+        let corners = distance::dijkstra::calculate(&grid, (0, 0), (9, 9));
+        assert!(longest[exit.x][exit.y].distance() >= corners[9][9].distance());
+    }
+
+    #[test]
+    fn test_place_longest_path_needs_two_linked_boundary_cells() {
+        let mut grid: Grid<BaseCell> = Grid::new(3, 3);
+        grid.place_longest_path();
+        assert!(grid.entrance().is_none());
+        assert!(grid.exit().is_none());
+
+        let mut grid: Grid<BaseCell> = Grid::new(1, 1);
+        grid.generate_aldous_broder();
+        grid.place_longest_path();
+        assert!(grid.entrance().is_none());
+    }
+
+    #[test]
+    fn test_place_longest_path_skips_masked_cells() {
+        for _ in 0..10 {
+            let mut grid: Grid<BaseCell> = Grid::new(4, 4);
+            grid.set_masked(0, 0, true);
+            grid.set_masked(3, 3, true);
+            grid.generate_aldous_broder();
+            grid.place_longest_path();
+
+            let entrance = grid.entrance().unwrap();
+            let exit = grid.exit().unwrap();
+            assert!(!grid.is_masked(entrance.x, entrance.y));
+            assert!(!grid.is_masked(exit.x, exit.y));
+            assert!(entrance.cell() != exit.cell());
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod corners;
+pub mod longest_path;
+pub mod portals;
+pub mod random;
@@ -0,0 +1,82 @@
+extern crate rand;
+
+use rand::Rng;
+
+use super::super::types::cell::Cell;
+use super::super::types::grid::Grid;
+
+/// Links `count` random pairs of cells which aren't adjacent by two-way
+/// portals. Every cell is the end of one portal at most, so there may be
+/// fewer portals on small grids.
+pub fn place<T>(grid: &mut Grid<T>, count: usize)
+    where T: Cell + Clone
+{
+    let mut taken: Vec<(usize, usize)> = Vec::new();
+    for &(from, to) in grid.portals().iter() {
+        taken.push(from);
+        taken.push(to);
+    }
+
+    let mut cells: Vec<(usize, usize)> = Vec::new();
+    for x in 0..grid.x() {
+        for y in 0..grid.y() {
+            if !taken.contains(&(x, y)) {
+                cells.push((x, y));
+            }
+        }
+    }
+
+    let mut rng = rand::thread_rng();
+    rng.shuffle(&mut cells);
+
+    let mut placed = 0;
+    while placed < count {
+        let from = match cells.pop() {
+            Some(cell) => cell,
+            None => break
+        };
+
+        let neighbors = grid.shape().neighbors(from.0, from.1);
+        match cells.iter().position(|cell| !neighbors.contains(cell)) {
+            Some(index) => {
+                let to = cells.remove(index);
+                grid.link_indices(from.0, from.1, to.0, to.1);
+                placed += 1;
+            },
+            None => break
+        }
+    }
+
+    debug!("Placed {} portals", placed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::distance;
+    use super::super::super::types::cell::BaseCell;
+    use super::super::super::types::grid::Grid;
+
+    #[test]
+    fn test_portals_shorten_paths() {
+        // Corridor along the top row, then a portal from its start to its end
+        let mut grid: Grid<BaseCell> = Grid::new(6, 1);
+        for x in 0..5 {
+            grid.link_indices(x, 0, x + 1, 0);
+        }
+        grid.link_indices(0, 0, 5, 0);
+        assert_eq!(grid.portals(), vec![((0, 0), (5, 0))]);
+
+        let distances = distance::dijkstra::distances(&grid, 0);
+        assert_eq!(distances.get(5), Some(1));
+        assert_eq!(distances.path_to(5), vec![0, 5]);
+        assert_eq!(grid.neighbors_linked_indices(0, 0).len(), 2);
+    }
+
+    #[test]
+    fn test_place() {
+        let mut grid: Grid<BaseCell> = Grid::new(5, 5);
+        grid.generate_aldous_broder();
+        grid.place_portals(3);
+        assert_eq!(grid.portals().len(), 3);
+    }
+}
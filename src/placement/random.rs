@@ -0,0 +1,149 @@
+extern crate rand;
+
+use rand::Rng;
+
+use super::super::distance;
+use super::super::types::cell::Cell;
+use super::super::types::grid::Grid;
+use super::super::types::opening::Opening;
+use super::super::types::rectangle::Direction;
+
+/// Cells in the outer wall along the given sides, each with the sides of
+/// its own which can be opened. Corners are listed once, masked cells not
+/// at all.
+fn boundary<T>(grid: &Grid<T>, sides: &[Direction]) -> Vec<((usize, usize), Vec<Direction>)>
+    where T: Cell + Clone
+{
+    let mut res = Vec::new();
+    for y in 0..grid.y() {
+        for x in 0..grid.x() {
+            let open: Vec<Direction> = sides.iter().cloned().filter(|&side| match side {
+                Direction::North => y == 0,
+                Direction::South => y == grid.y() - 1,
+                Direction::West => x == 0,
+                Direction::East => x == grid.x() - 1
+            }).collect();
+
+            if !open.is_empty() && !grid.is_masked(x, y) {
+                res.push(((x, y), open));
+            }
+        }
+    }
+
+    res
+}
+
+fn random_opening(boundary: &[((usize, usize), Vec<Direction>)]) -> Opening {
+    let mut rng = rand::thread_rng();
+    let &((x, y), ref sides) = rng.choose(boundary).unwrap();
+    Opening::new(x, y, *rng.choose(sides).unwrap())
+}
+
+/// Entrance on a uniformly chosen boundary cell, exit on another one chosen
+/// uniformly among those linked to it, each in a random side of the cell
+/// where it has more than one (corners). Edges which wrap around have no
+/// outer wall to open. No openings are placed when the entrance can't reach
+/// another boundary cell, such as in a maze not carved yet.
+pub fn place<T>(grid: &mut Grid<T>)
+    where T: Cell + Clone
+{
+    let mut sides = Vec::new();
+    if !grid.shape().wrap().wraps_y() {
+        sides.push(Direction::North);
+        sides.push(Direction::South);
+    }
+
+    if !grid.shape().wrap().wraps_x() {
+        sides.push(Direction::East);
+        sides.push(Direction::West);
+    }
+
+    if sides.is_empty() {
+        grid.set_entrance(None);
+        grid.set_exit(None);
+        return;
+    }
+
+    let boundary = boundary(grid, &sides);
+    if boundary.is_empty() {
+        grid.set_entrance(None);
+        grid.set_exit(None);
+        return;
+    }
+
+    let entrance = random_opening(&boundary);
+    let distances = distance::dijkstra::distances(grid, grid.shape().id(entrance.x, entrance.y));
+    let reachable: Vec<((usize, usize), Vec<Direction>)> = boundary.into_iter()
+        .filter(|&(cell, _)| cell != entrance.cell() && distances.get(grid.shape().id(cell.0, cell.1)).is_some())
+        .collect();
+
+    if reachable.is_empty() {
+        grid.set_entrance(None);
+        grid.set_exit(None);
+        return;
+    }
+
+    grid.set_entrance(Some(entrance));
+    grid.set_exit(Some(random_opening(&reachable)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::types::cell::BaseCell;
+    use super::super::super::types::rectangle::Wrap;
+
+    #[test]
+    fn test_boundary_lists_corners_once() {
+        let grid: Grid<BaseCell> = Grid::new(4, 3);
+        let cells = boundary(&grid, &[Direction::North, Direction::South, Direction::East, Direction::West]);
+        assert_eq!(cells.len(), 10);
+        assert_eq!(cells[0], ((0, 0), vec![Direction::North, Direction::West]));
+
+        let mut grid: Grid<BaseCell> = Grid::with_wrap(4, 3, Wrap::Cylinder);
+        grid.generate_aldous_broder();
+        place(&mut grid);
+        let entrance = grid.entrance().unwrap();
+        assert!(entrance.side == Direction::North || entrance.side == Direction::South);
+    }
+
+    #[test]
+    fn test_place_needs_two_linked_boundary_cells() {
+        let mut grid: Grid<BaseCell> = Grid::new(3, 3);
+        place(&mut grid);
+        assert!(grid.entrance().is_none());
+        assert!(grid.exit().is_none());
+
+        let mut grid: Grid<BaseCell> = Grid::new(1, 1);
+        place(&mut grid);
+        assert!(grid.entrance().is_none());
+    }
+
+    #[test]
+    fn test_place_skips_masked_cells() {
+        let mut grid: Grid<BaseCell> = Grid::new(3, 2);
+        for x in 0..3 {
+            grid.set_masked(x, 0, true);
+        }
+        grid.set_masked(1, 1, true);
+
+        // Only the two cells left at the ends of the bottom row are on the
+        // boundary, and the masked cell between them keeps them apart
+        place(&mut grid);
+        assert!(grid.entrance().is_none());
+
+        for _ in 0..10 {
+            let mut grid: Grid<BaseCell> = Grid::new(4, 4);
+            grid.set_masked(0, 0, true);
+            grid.set_masked(3, 3, true);
+            grid.generate_aldous_broder();
+            place(&mut grid);
+
+            let entrance = grid.entrance().unwrap();
+            let exit = grid.exit().unwrap();
+            assert!(!grid.is_masked(entrance.x, entrance.y));
+            assert!(!grid.is_masked(exit.x, exit.y));
+            assert!(entrance.cell() != exit.cell());
+        }
+    }
+}
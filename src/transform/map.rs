@@ -0,0 +1,293 @@
+use super::super::error::MazeError;
+use super::super::types::cell::Cell;
+use super::super::types::grid::Grid;
+use super::super::types::opening::Opening;
+use super::super::types::rectangle::{Direction, Rectangle, Wrap};
+
+/// Copies the cells, weights, metadata, mask and links of `from` into `to`,
+/// moving cell (x, y) to `map((x, y))` and leaving out cells mapped to
+/// `None`.
+///
+/// A link, or the weight of one, is kept when both of its cells are, and
+/// when they are still adjacent or never were (portals). Passages across a
+/// joined edge which doesn't exist in `to` are dropped.
+pub fn copy<T, F>(from: &Grid<T>, to: &mut Grid<T>, map: F)
+    where T: Cell + Clone, F: Fn((usize, usize)) -> Option<(usize, usize)>
+{
+    for x in 0..from.x() {
+        for y in 0..from.y() {
+            let (nx, ny) = match map((x, y)) {
+                Some(cell) => cell,
+                None => continue
+            };
+
+            to.cells[nx][ny] = from.cells[x][y].moved_to(nx, ny);
+            to.set_weight(nx, ny, from.weight(x, y));
+            to.set_masked(nx, ny, from.is_masked(x, y));
+            if let Some(metadata) = from.metadata(x, y) {
+                for (key, value) in metadata.iter() {
+                    to.set_metadata(nx, ny, &key[..], &value[..]);
+                }
+            }
+        }
+    }
+
+    let shape = *to.shape();
+    let moved = |a: (usize, usize), b: (usize, usize)| {
+        match (map(a), map(b)) {
+            (Some(na), Some(nb)) => {
+                let portal = !from.shape().neighbors(a.0, a.1).contains(&b);
+                match portal || shape.neighbors(na.0, na.1).contains(&nb) {
+                    true => Some((na, nb)),
+                    false => None
+                }
+            },
+            _ => None
+        }
+    };
+
+    for (a, b) in from.links.pairs() {
+        if let Some((na, nb)) = moved(a, b) {
+            to.link_pair(na.0, na.1, nb.0, nb.1);
+        }
+    }
+
+    // Weights of links which aren't carved (yet) are kept too
+    for ((a, b), weight) in from.link_weights() {
+        if let Some((na, nb)) = moved(a, b) {
+            to.set_link_weight(na.0, na.1, nb.0, nb.1, weight);
+        }
+    }
+}
+
+/// Whether an opening on the given side of the cell goes through the outer
+/// wall of the grid.
+pub fn is_outer(shape: &Rectangle, x: usize, y: usize, side: Direction) -> bool {
+    shape.neighbor(x, y, side).is_none()
+}
+
+/// New `x * y` grid with everything of `grid` moved by `map`, and the sides
+/// of its openings turned by `side`. Openings which don't end up in the
+/// outer wall are dropped. The seed is dropped too, it no longer carves the
+/// same maze.
+fn remap<T, F, S>(grid: &Grid<T>, x: usize, y: usize, wrap: Wrap, map: F, side: S) -> Grid<T>
+    where T: Cell + Clone, F: Fn((usize, usize)) -> Option<(usize, usize)>, S: Fn(Direction) -> Direction
+{
+    let mut res: Grid<T> = Grid::with_wrap(x, y, wrap);
+    copy(grid, &mut res, &map);
+
+    let moved = |opening: Option<Opening>| {
+        opening.and_then(|opening| map(opening.cell()).map(|(x, y)| Opening::new(x, y, side(opening.side))))
+            .and_then(|opening| match is_outer(res.shape(), opening.x, opening.y, opening.side) {
+                true => Some(opening),
+                false => None
+            })
+    };
+
+    let entrance = moved(grid.entrance());
+    let exit = moved(grid.exit());
+    res.set_entrance(entrance);
+    res.set_exit(exit);
+    res.set_algorithm(grid.algorithm().map(|algorithm| algorithm.to_string()));
+
+    res
+}
+
+/// Wrap of the grid once its axes are swapped, if there is one.
+fn swapped_wrap(wrap: Wrap) -> Result<Wrap, MazeError> {
+    match wrap {
+        Wrap::None | Wrap::Torus => Ok(wrap),
+        _ => Err(MazeError::Transform(format!("{} grids can't be turned on their side", wrap.name())))
+    }
+}
+
+/// Part of the grid with its top left cell at (x, y). Openings and links
+/// leading out of it are dropped, the part doesn't wrap.
+pub fn crop<T>(grid: &Grid<T>, x: usize, y: usize, width: usize, height: usize) -> Result<Grid<T>, MazeError>
+    where T: Cell + Clone
+{
+    if width == 0 || height == 0 {
+        return Err(MazeError::EmptyGrid(width, height));
+    }
+
+    // Corners far beyond the grid would overflow
+    let (last_x, last_y) = match (x.checked_add(width - 1), y.checked_add(height - 1)) {
+        (Some(last_x), Some(last_y)) => (last_x, last_y),
+        _ => return Err(MazeError::Transform(format!("{}x{} cells at ({}, {}) are out of range", width, height, x, y)))
+    };
+
+    if let Err(e) = grid.check_indices(last_x, last_y) {
+        return Err(e);
+    }
+
+    Ok(remap(grid, width, height, Wrap::None, |(cx, cy)| {
+        match cx >= x && cx < x + width && cy >= y && cy < y + height {
+            true => Some((cx - x, cy - y)),
+            false => None
+        }
+    }, |side| side))
+}
+
+/// Grid flipped upside down, north becoming south.
+pub fn flip<T>(grid: &Grid<T>) -> Grid<T>
+    where T: Cell + Clone
+{
+    let (x, y) = (grid.x(), grid.y());
+    remap(grid, x, y, grid.shape().wrap(), |(cx, cy)| Some((cx, y - 1 - cy)), |side| {
+        match side {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            side => side
+        }
+    })
+}
+
+/// Grid mirrored left to right, east becoming west.
+pub fn mirror<T>(grid: &Grid<T>) -> Grid<T>
+    where T: Cell + Clone
+{
+    let (x, y) = (grid.x(), grid.y());
+    remap(grid, x, y, grid.shape().wrap(), |(cx, cy)| Some((x - 1 - cx, cy)), |side| {
+        match side {
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+            side => side
+        }
+    })
+}
+
+/// Grid rotated clockwise by `quarter_turns` times 90 degrees. Grids with
+/// only one pair of edges joined can be rotated by 180 degrees only.
+pub fn rotate<T>(grid: &Grid<T>, quarter_turns: usize) -> Result<Grid<T>, MazeError>
+    where T: Cell + Clone
+{
+    let (x, y) = (grid.x(), grid.y());
+    let wrap = grid.shape().wrap();
+
+    match quarter_turns % 4 {
+        0 => Ok(remap(grid, x, y, wrap, |cell| Some(cell), |side| side)),
+        1 => swapped_wrap(wrap).map(|wrap| remap(grid, y, x, wrap, |(cx, cy)| Some((y - 1 - cy, cx)), |side| {
+            match side {
+                Direction::North => Direction::East,
+                Direction::East => Direction::South,
+                Direction::South => Direction::West,
+                Direction::West => Direction::North
+            }
+        })),
+        2 => Ok(remap(grid, x, y, wrap, |(cx, cy)| Some((x - 1 - cx, y - 1 - cy)), |side| {
+            match side {
+                Direction::North => Direction::South,
+                Direction::East => Direction::West,
+                Direction::South => Direction::North,
+                Direction::West => Direction::East
+            }
+        })),
+        _ => swapped_wrap(wrap).map(|wrap| remap(grid, y, x, wrap, |(cx, cy)| Some((cy, x - 1 - cx)), |side| {
+            match side {
+                Direction::North => Direction::West,
+                Direction::East => Direction::North,
+                Direction::South => Direction::East,
+                Direction::West => Direction::South
+            }
+        }))
+    }
+}
+
+/// Grid mirrored along its diagonal from the top left corner, rows becoming
+/// columns.
+pub fn transpose<T>(grid: &Grid<T>) -> Result<Grid<T>, MazeError>
+    where T: Cell + Clone
+{
+    let (x, y) = (grid.x(), grid.y());
+    swapped_wrap(grid.shape().wrap()).map(|wrap| remap(grid, y, x, wrap, |(cx, cy)| Some((cy, cx)), |side| {
+        match side {
+            Direction::North => Direction::West,
+            Direction::East => Direction::South,
+            Direction::South => Direction::East,
+            Direction::West => Direction::North
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::types::cell::BaseCell;
+
+    fn sample() -> Grid<BaseCell<u32>> {
+        let mut grid: Grid<BaseCell<u32>> = Grid::new(3, 2);
+        grid.generate_aldous_broder();
+        grid.place_corners();
+        grid[2][0].payload = 7;
+        grid
+    }
+
+    #[test]
+    fn test_rotate() {
+        let grid = sample();
+        let rotated = rotate(&grid, 1).unwrap();
+        assert_eq!((rotated.x(), rotated.y()), (2, 3));
+        assert_eq!(rotated[1][2].payload, 7);
+        assert_eq!(rotated.entrance(), Some(Opening::new(1, 0, Direction::North)));
+        assert_eq!(rotated.links.pairs().len(), grid.links.pairs().len());
+
+        // Four quarter turns, or two half turns, give the same maze back
+        let back = rotate(&rotate(&rotate(&rotated, 1).unwrap(), 1).unwrap(), 1).unwrap();
+        assert_eq!(back.links, grid.links);
+        assert_eq!(rotate(&rotate(&grid, 2).unwrap(), 2).unwrap().links, grid.links);
+
+        assert!(rotate(&Grid::<BaseCell>::with_wrap(3, 2, Wrap::Cylinder), 1).is_err());
+    }
+
+    #[test]
+    fn test_rotate_keeps_weights_and_mask() {
+        let mut grid = sample();
+        grid.set_masked(1, 1, true);
+        grid.set_weight(1, 1, 5);
+        grid.set_weight(2, 1, 3);
+        grid.set_link_weight(0, 0, 1, 0, 4);
+        grid.unlink_indices(0, 0, 1, 0);
+
+        let mut back = grid.clone();
+        for _ in 0..4 {
+            back = back.rotated(1).unwrap();
+        }
+
+        assert_eq!(back, grid);
+        assert_eq!(back.mask(), grid.mask());
+        assert_eq!(back.link_weights(), grid.link_weights());
+        for x in 0..grid.x() {
+            for y in 0..grid.y() {
+                assert_eq!(back.weight(x, y), grid.weight(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_mirror_flip_transpose() {
+        let grid = sample();
+        assert_eq!(mirror(&mirror(&grid)).links, grid.links);
+        assert_eq!(flip(&flip(&grid)).links, grid.links);
+        assert_eq!(transpose(&transpose(&grid).unwrap()).unwrap().links, grid.links);
+        assert_eq!(mirror(&grid)[0][0].payload, 7);
+        assert_eq!(mirror(&grid).exit(), Some(Opening::new(0, 1, Direction::West)));
+
+        // Rotating is transposing and mirroring
+        assert_eq!(rotate(&grid, 1).unwrap().links, mirror(&transpose(&grid).unwrap()).links);
+    }
+
+    #[test]
+    fn test_crop() {
+        let mut grid: Grid<BaseCell> = Grid::new(4, 4);
+        grid.link_indices(1, 1, 2, 1);
+        grid.link_indices(2, 1, 3, 1);
+        grid.link_indices(1, 1, 3, 3);
+
+        let cropped = crop(&grid, 1, 1, 2, 2).unwrap();
+        assert_eq!(cropped.links.pairs(), vec![((0, 0), (1, 0)), ((1, 0), (0, 0))]);
+        assert!(crop(&grid, 3, 3, 2, 1).is_err());
+        assert!(crop(&grid, 0, 0, 0, 1).is_err());
+        assert!(crop(&grid, ::std::usize::MAX, 0, 2, 1).is_err());
+        assert!(crop(&grid, 0, ::std::usize::MAX, 1, 2).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+extern crate rand;
+
+use rand::Rng;
+
+use super::map;
+use super::super::error::MazeError;
+use super::super::types::cell::Cell;
+use super::super::types::grid::Grid;
+use super::super::types::opening::Opening;
+use super::super::types::rectangle::Wrap;
+
+/// Opens `openings` passages at random along a shared edge of `length`
+/// cells, at most one per cell. `cells(i)` gives the pair of cells on
+/// either side of the edge at position `i`. Positions with a masked cell on
+/// either side are never opened.
+fn join<T, F>(grid: &mut Grid<T>, length: usize, openings: usize, cells: F)
+    where T: Cell + Clone, F: Fn(usize) -> ((usize, usize), (usize, usize))
+{
+    let mut positions: Vec<usize> = (0..length).filter(|&i| {
+        let (a, b) = cells(i);
+        !grid.is_masked(a.0, a.1) && !grid.is_masked(b.0, b.1)
+    }).collect();
+    rand::thread_rng().shuffle(&mut positions);
+
+    for &i in positions.iter().take(openings) {
+        let (a, b) = cells(i);
+        grid.link_indices(a.0, a.1, b.0, b.1);
+    }
+}
+
+/// Grid of the tiles `tiles[row][column]` put side by side, with `openings`
+/// passages opened at random along every edge shared by two tiles, so with
+/// at least one the whole maze stays connected. Masked cells are left out
+/// of the passages, tiles with an edge masked all along stay apart there.
+///
+/// Tiles in the same row need the same height and tiles in the same column
+/// the same width. The entrance of the top left tile and the exit of the
+/// bottom right one are kept where they are still in the outer wall.
+pub fn tile<T>(tiles: &[Vec<Grid<T>>], openings: usize) -> Result<Grid<T>, MazeError>
+    where T: Cell + Clone
+{
+    let columns = match tiles.first() {
+        Some(row) if !row.is_empty() => row.len(),
+        _ => return Err(MazeError::Transform("no tiles to join".to_string()))
+    };
+
+    let widths: Vec<usize> = tiles[0].iter().map(|tile| tile.x()).collect();
+    let heights: Vec<usize> = tiles.iter().map(|row| row[0].y()).collect();
+
+    for (i, row) in tiles.iter().enumerate() {
+        if row.len() != columns {
+            return Err(MazeError::Transform(format!("row {} has {} tiles instead of {}", i, row.len(), columns)));
+        }
+
+        for (j, tile) in row.iter().enumerate() {
+            if tile.x() != widths[j] || tile.y() != heights[i] {
+                return Err(MazeError::Transform(format!("tile ({}, {}) is {}x{} instead of {}x{}", j, i, tile.x(), tile.y(), widths[j], heights[i])));
+            }
+
+            if tile.shape().wrap() != Wrap::None {
+                return Err(MazeError::Transform(format!("tile ({}, {}) wraps around its edges", j, i)));
+            }
+        }
+    }
+
+    // Offsets of the columns and rows
+    let xs: Vec<usize> = (0..columns + 1).map(|j| widths[..j].iter().sum()).collect();
+    let ys: Vec<usize> = (0..tiles.len() + 1).map(|i| heights[..i].iter().sum()).collect();
+
+    let mut res: Grid<T> = Grid::new(xs[columns], ys[tiles.len()]);
+    for (i, row) in tiles.iter().enumerate() {
+        for (j, tile) in row.iter().enumerate() {
+            map::copy(tile, &mut res, |(x, y)| Some((xs[j] + x, ys[i] + y)));
+        }
+    }
+
+    // Joined once every tile is in place, so masks on both sides are known
+    for i in 0..tiles.len() {
+        for j in 0..columns {
+            if j + 1 < columns {
+                let x = xs[j + 1];
+                join(&mut res, heights[i], openings, |k| ((x - 1, ys[i] + k), (x, ys[i] + k)));
+            }
+
+            if i + 1 < tiles.len() {
+                let y = ys[i + 1];
+                join(&mut res, widths[j], openings, |k| ((xs[j] + k, y - 1), (xs[j] + k, y)));
+            }
+        }
+    }
+
+    let first = &tiles[0][0];
+    if let Some(entrance) = first.entrance() {
+        if map::is_outer(res.shape(), entrance.x, entrance.y, entrance.side) {
+            res.set_entrance(Some(entrance));
+        }
+    }
+
+    let (last_i, last_j) = (tiles.len() - 1, columns - 1);
+    if let Some(exit) = tiles[last_i][last_j].exit() {
+        let (x, y) = (xs[last_j] + exit.x, ys[last_i] + exit.y);
+        if map::is_outer(res.shape(), x, y, exit.side) {
+            res.set_exit(Some(Opening::new(x, y, exit.side)));
+        }
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::distance;
+    use super::super::super::types::cell::BaseCell;
+
+    fn carved(x: usize, y: usize) -> Grid<BaseCell> {
+        let mut grid: Grid<BaseCell> = Grid::new(x, y);
+        grid.generate_aldous_broder();
+        grid.place_corners();
+        grid
+    }
+
+    #[test]
+    fn test_tile_is_connected() {
+        let grid = carved(3, 2);
+        let tiled = tile(&vec![vec![grid.clone(), grid.clone()], vec![grid.clone(), grid.clone()]], 1).unwrap();
+        assert_eq!((tiled.x(), tiled.y()), (6, 4));
+        // 4 spanning trees of 6 cells each joined by 4 openings
+        assert_eq!(tiled.links.pairs().len() / 2, 4 * 5 + 4);
+
+        let distances = distance::dijkstra::distances(&tiled, 0);
+        for id in 0..tiled.size() {
+            assert!(distances.get(id).is_some());
+        }
+
+        assert_eq!(tiled.entrance(), grid.entrance());
+        assert_eq!(tiled.exit().map(|exit| exit.cell()), Some((5, 3)));
+    }
+
+    #[test]
+    fn test_tile_leaves_masked_cells_out() {
+        let mut grid: Grid<BaseCell> = Grid::new(3, 2);
+        grid.set_masked(2, 0, true);
+        grid.set_masked(0, 1, true);
+        grid.generate_aldous_broder();
+
+        // East edges are masked on one side all along, south edges only
+        // have the middle column to open
+        let tiled = tile(&vec![vec![grid.clone(), grid.clone()], vec![grid.clone(), grid.clone()]], 3).unwrap();
+        for (a, b) in tiled.links.pairs() {
+            assert!(!tiled.is_masked(a.0, a.1) && !tiled.is_masked(b.0, b.1), "{:?} - {:?} links a masked cell", a, b);
+        }
+        assert_eq!(tiled.links.pairs().len() / 2, 4 * 3 + 2);
+        assert_eq!(tiled.validate().components.len(), 2);
+    }
+
+    #[test]
+    fn test_tile_rejects_mismatched_tiles() {
+        assert!(tile(&vec![vec![carved(3, 2), carved(3, 3)]], 1).is_err());
+        assert!(tile(&vec![vec![carved(3, 2)], vec![carved(2, 2)]], 1).is_err());
+        assert!(tile::<BaseCell>(&vec![], 1).is_err());
+        assert!(tile(&vec![vec![carved(3, 2), carved(2, 2)]], 1).is_ok());
+    }
+}
@@ -1,30 +1,49 @@
 // use std::collections::HashMap;
 
+/// Position of a cell in a grid. Anything else the cell carries is up to
+/// the implementation, drawing it is up to the output modules.
 pub trait Cell {
     fn new(x: usize, y: usize) -> Self;
-    fn to_string(&self) -> String;
     fn x(&self) -> usize;
     fn y(&self) -> usize;
+
+    /// Same cell at another position, as when a grid is rotated. Anything
+    /// the cell carries is lost unless the implementation keeps it.
+    fn moved_to(&self, x: usize, y: usize) -> Self
+        where Self: Sized
+    {
+        Self::new(x, y)
+    }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub struct BaseCell {
+/// Cell with a payload of any type, such as the items, tags or room id of
+/// a game, created by `Default` and set through `grid[x][y].payload`.
+///
+/// `BaseCell` alone carries nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BaseCell<P = ()> {
     x: usize,
-    y: usize
+    y: usize,
+    pub payload: P
 }
 
-const EMPTY_CELL: &'static str = "   ";
-
-impl Cell for BaseCell {
-    fn new(x: usize, y: usize) -> BaseCell {
+impl <P> Cell for BaseCell<P>
+    where P: Clone + Default
+{
+    fn new(x: usize, y: usize) -> BaseCell<P> {
         BaseCell {
             x: x,
-            y: y
+            y: y,
+            payload: P::default()
         }
     }
 
-    fn to_string(&self) -> String {
-        EMPTY_CELL.to_string()
+    fn moved_to(&self, x: usize, y: usize) -> BaseCell<P> {
+        BaseCell {
+            x: x,
+            y: y,
+            payload: self.payload.clone()
+        }
     }
 
     fn x(&self) -> usize {
@@ -35,3 +54,26 @@ impl Cell for BaseCell {
         self.y
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::grid::Grid;
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct Room {
+        name: String,
+        items: Vec<&'static str>
+    }
+
+    #[test]
+    fn test_payload() {
+        let mut grid: Grid<BaseCell<Room>> = Grid::new(2, 2);
+        grid[1][0].payload.name = "armory".to_string();
+        grid[1][0].payload.items.push("sword");
+
+        assert_eq!(grid[1][0].payload.items, vec!["sword"]);
+        assert_eq!(grid[0][1].payload, Room::default());
+        assert_eq!((grid[1][0].x(), grid[1][0].y()), (1, 0));
+    }
+}
@@ -0,0 +1,219 @@
+use super::maze::Maze;
+use super::topology::Topology;
+use super::super::error::MazeError;
+use super::super::output;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeDirection {
+    North,
+    East,
+    South,
+    West
+}
+
+pub const CUBE_DIRECTIONS: [CubeDirection; 4] = [
+    CubeDirection::North,
+    CubeDirection::East,
+    CubeDirection::South,
+    CubeDirection::West
+];
+
+type Vector = [isize; 3];
+
+/// Face of the cube, placed in space by its corner and the directions of
+/// its x and y axes. Coordinates are in half-cells, so a cube of `n` cells
+/// spans `0..2n` and cell centers have odd coordinates.
+struct Face {
+    /// Corner in units of `2n`
+    origin: Vector,
+    right: Vector,
+    down: Vector,
+    /// Position in the unfolded net, in faces
+    net: (usize, usize)
+}
+
+/// Faces as laid out in the net
+///
+/// ```text
+///     U
+///   L F R B
+///     D
+/// ```
+///
+/// with x growing right and y growing down in the net, all seen from outside.
+const FACES: [Face; 6] = [
+    // Front
+    Face { origin: [0, 0, 1], right: [1, 0, 0], down: [0, 1, 0], net: (1, 1) },
+    // Right
+    Face { origin: [1, 0, 1], right: [0, 0, -1], down: [0, 1, 0], net: (2, 1) },
+    // Back
+    Face { origin: [1, 0, 0], right: [-1, 0, 0], down: [0, 1, 0], net: (3, 1) },
+    // Left
+    Face { origin: [0, 0, 0], right: [0, 0, 1], down: [0, 1, 0], net: (0, 1) },
+    // Up
+    Face { origin: [0, 0, 0], right: [1, 0, 0], down: [0, 0, 1], net: (1, 0) },
+    // Down
+    Face { origin: [0, 1, 1], right: [1, 0, 0], down: [0, 0, -1], net: (1, 2) }
+];
+
+fn add(a: Vector, b: Vector, scale: isize) -> Vector {
+    [a[0] + b[0] * scale, a[1] + b[1] * scale, a[2] + b[2] * scale]
+}
+
+fn cross(a: Vector, b: Vector) -> Vector {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn dot(a: Vector, b: Vector) -> isize {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Surface of a cube made of six `n x n` faces.
+///
+/// Cells are addressed by `(face, x, y)`, faces are numbered front, right,
+/// back, left, up, down. Ids are assigned face by face, then row by row,
+/// `id = (face * n + y) * n + x`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cube {
+    n: usize
+}
+
+impl Cube {
+    pub fn new(n: usize) -> Cube {
+        Cube {
+            n: n
+        }
+    }
+
+    fn center(&self, face: usize, x: usize, y: usize) -> Vector {
+        let f = &FACES[face];
+        let corner = [f.origin[0] * 2 * self.n as isize, f.origin[1] * 2 * self.n as isize, f.origin[2] * 2 * self.n as isize];
+        add(add(corner, f.right, 2 * x as isize + 1), f.down, 2 * y as isize + 1)
+    }
+
+    /// Cell on the given face whose center is at `point`, if any.
+    fn locate(&self, face: usize, point: Vector) -> Option<(usize, usize)> {
+        let f = &FACES[face];
+        let corner = [f.origin[0] * 2 * self.n as isize, f.origin[1] * 2 * self.n as isize, f.origin[2] * 2 * self.n as isize];
+        let relative = add(point, corner, -1);
+
+        let u = dot(relative, f.right);
+        let v = dot(relative, f.down);
+        let size = 2 * self.n as isize;
+        if u < 0 || v < 0 || u >= size || v >= size || dot(relative, cross(f.right, f.down)) != 0 {
+            return None;
+        }
+
+        Some(((u / 2) as usize, (v / 2) as usize))
+    }
+
+    pub fn coords(&self, id: usize) -> (usize, usize, usize) {
+        let face = self.n * self.n;
+        (id / face, id % self.n, (id % face) / self.n)
+    }
+
+    pub fn id(&self, face: usize, x: usize, y: usize) -> usize {
+        (face * self.n + y) * self.n + x
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Position of the face in the unfolded net, in faces.
+    pub fn net(&self, face: usize) -> (usize, usize) {
+        FACES[face].net
+    }
+
+    pub fn neighbor(&self, face: usize, x: usize, y: usize, direction: CubeDirection) -> (usize, usize, usize) {
+        let f = &FACES[face];
+        let step = match direction {
+            CubeDirection::North => add([0, 0, 0], f.down, -1),
+            CubeDirection::East => f.right,
+            CubeDirection::South => f.down,
+            CubeDirection::West => add([0, 0, 0], f.right, -1)
+        };
+
+        let center = self.center(face, x, y);
+        if let Some((nx, ny)) = self.locate(face, add(center, step, 2)) {
+            return (face, nx, ny);
+        }
+
+        // Over the edge, half a cell to it and half a cell down the face
+        // whose normal points the way we were going
+        let normal = cross(f.right, f.down);
+        let point = add(add(center, step, 1), normal, -1);
+        for (other, g) in FACES.iter().enumerate() {
+            if cross(g.right, g.down) == step {
+                let (nx, ny) = self.locate(other, point).unwrap();
+                return (other, nx, ny);
+            }
+        }
+
+        unreachable!()
+    }
+
+    pub fn neighbors(&self, face: usize, x: usize, y: usize) -> Vec<(usize, usize, usize)> {
+        CUBE_DIRECTIONS.iter().map(|&d| self.neighbor(face, x, y, d)).collect()
+    }
+}
+
+impl Topology for Cube {
+    fn size(&self) -> usize {
+        6 * self.n * self.n
+    }
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        let (face, x, y) = self.coords(id);
+        self.neighbors(face, x, y).iter().map(|&(f, nx, ny)| self.id(f, nx, ny)).collect()
+    }
+
+    /// The surface of a cube has no edge.
+    fn is_boundary_id(&self, _id: usize) -> bool {
+        false
+    }
+}
+
+impl Maze<Cube> {
+    pub fn to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+        self.try_to_png(cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+    }
+
+    /// Same as `to_png`, failing when the image can't be written.
+    pub fn try_to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+        output::png_cube::try_format(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::topology::Topology;
+
+    #[test]
+    fn test_neighbors_are_symmetric() {
+        let cube = Cube::new(3);
+        for id in 0..cube.size() {
+            let neighbors = cube.neighbor_ids(id);
+            assert_eq!(neighbors.len(), 4);
+            for n in neighbors {
+                assert!(cube.neighbor_ids(n).contains(&id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_edges_keep_orientation() {
+        let cube = Cube::new(3);
+
+        // Front and right share a vertical edge
+        assert_eq!(cube.neighbor(0, 2, 1, CubeDirection::East), (1, 0, 1));
+
+        // Front and up share the top edge of the front face
+        assert_eq!(cube.neighbor(0, 1, 0, CubeDirection::North), (4, 1, 2));
+
+        // Right and up, the top edge of the right face runs along the up face
+        assert_eq!(cube.neighbor(1, 0, 0, CubeDirection::North), (4, 2, 2));
+        assert_eq!(cube.neighbor(1, 2, 0, CubeDirection::North), (4, 2, 0));
+    }
+}
@@ -0,0 +1,116 @@
+use super::maze::Maze;
+use super::topology::Topology;
+use super::super::error::MazeError;
+use super::super::output;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaDirection {
+    North,
+    East,
+    South,
+    West
+}
+
+/// Rows of alternating upward and downward pointing triangles.
+///
+/// Cell (x, y) points upward when `x + y` is even. Ids are assigned as on a
+/// `Rectangle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delta {
+    x: usize,
+    y: usize
+}
+
+impl Delta {
+    pub fn new(x: usize, y: usize) -> Delta {
+        Delta {
+            x: x,
+            y: y
+        }
+    }
+
+    pub fn coords(&self, id: usize) -> (usize, usize) {
+        (id % self.x, id / self.x)
+    }
+
+    /// Directions of the three edges of the cell.
+    pub fn directions(&self, x: usize, y: usize) -> [DeltaDirection; 3] {
+        match self.is_upright(x, y) {
+            true => [DeltaDirection::West, DeltaDirection::East, DeltaDirection::South],
+            false => [DeltaDirection::West, DeltaDirection::East, DeltaDirection::North]
+        }
+    }
+
+    pub fn id(&self, x: usize, y: usize) -> usize {
+        y * self.x + x
+    }
+
+    pub fn is_upright(&self, x: usize, y: usize) -> bool {
+        (x + y) % 2 == 0
+    }
+
+    pub fn neighbor(&self, x: usize, y: usize, direction: DeltaDirection) -> Option<(usize, usize)> {
+        match direction {
+            DeltaDirection::West if x > 0 => Some((x - 1, y)),
+            DeltaDirection::East if x < self.x - 1 => Some((x + 1, y)),
+            DeltaDirection::North if !self.is_upright(x, y) && y > 0 => Some((x, y - 1)),
+            DeltaDirection::South if self.is_upright(x, y) && y < self.y - 1 => Some((x, y + 1)),
+            _ => None
+        }
+    }
+
+    pub fn neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        self.directions(x, y).iter().filter_map(|&d| self.neighbor(x, y, d)).collect()
+    }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn y(&self) -> usize {
+        self.y
+    }
+}
+
+impl Topology for Delta {
+    fn size(&self) -> usize {
+        self.x * self.y
+    }
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        let (x, y) = self.coords(id);
+        self.neighbors(x, y).iter().map(|&(nx, ny)| self.id(nx, ny)).collect()
+    }
+
+    fn is_boundary_id(&self, id: usize) -> bool {
+        let (x, y) = self.coords(id);
+        self.neighbors(x, y).len() < 3
+    }
+}
+
+impl Maze<Delta> {
+    pub fn to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+        self.try_to_png(cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+    }
+
+    /// Same as `to_png`, failing when the image can't be written.
+    pub fn try_to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+        output::png_delta::try_format(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::topology::Topology;
+
+    #[test]
+    fn test_neighbors_are_symmetric() {
+        let delta = Delta::new(6, 4);
+        for id in 0..delta.size() {
+            for n in delta.neighbor_ids(id) {
+                assert!(delta.neighbor_ids(n).contains(&id));
+            }
+        }
+    }
+}
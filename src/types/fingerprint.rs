@@ -0,0 +1,145 @@
+use super::cell::Cell;
+use super::grid::Grid;
+use super::super::transform;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// 64-bit FNV-1a, which unlike `std::hash` gives the same result with every
+/// version of Rust, so fingerprints can be stored.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(FNV_OFFSET)
+    }
+
+    fn write(&mut self, value: usize) {
+        for i in 0..8 {
+            self.0 ^= (value as u64 >> (i * 8)) & 0xff;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/// Fingerprint of the size, wrap and links of the grid, the same for grids
+/// which are equal. Openings, weights and payloads are left out.
+pub fn fingerprint<T>(grid: &Grid<T>) -> u64
+    where T: Cell + Clone
+{
+    let mut hasher = Fnv::new();
+    hasher.write(grid.x());
+    hasher.write(grid.y());
+    hasher.write(grid.shape().wrap() as usize);
+
+    // Links come in order, whatever order they were added in
+    for (from, to) in grid.links.pairs() {
+        hasher.write(from.0);
+        hasher.write(from.1);
+        hasher.write(to.0);
+        hasher.write(to.1);
+    }
+
+    hasher.0
+}
+
+/// Same as `fingerprint`, the same for all rotations and mirror images of
+/// the grid as well. Wrapped grids which can't be rotated by 90 degrees
+/// count the rotations by 180 degrees and the mirror images only.
+pub fn canonical<T>(grid: &Grid<T>) -> u64
+    where T: Cell + Clone
+{
+    let mirrored = transform::map::mirror(grid);
+
+    let mut res = fingerprint(grid);
+    for quarter_turns in 0..4 {
+        for original in [grid, &mirrored].iter() {
+            if let Ok(rotated) = transform::map::rotate(original, quarter_turns) {
+                res = res.min(fingerprint(&rotated));
+            }
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use super::super::cell::BaseCell;
+
+    #[test]
+    fn test_fingerprint_ignores_link_order() {
+        let mut a: Grid<BaseCell> = Grid::new(3, 3);
+        a.link_indices(0, 0, 1, 0);
+        a.link_indices(1, 1, 1, 0);
+
+        let mut b: Grid<BaseCell> = Grid::new(3, 3);
+        b.link_indices(1, 0, 1, 1);
+        b.link_indices(1, 0, 0, 0);
+
+        assert_eq!(fingerprint(&a), fingerprint(&b));
+        assert_eq!(a, b);
+
+        b.link_indices(2, 2, 2, 1);
+        assert!(fingerprint(&a) != fingerprint(&b));
+        assert!(a != b);
+    }
+
+    #[test]
+    fn test_canonical_ignores_symmetries() {
+        let mut grid: Grid<BaseCell> = Grid::new(4, 3);
+        grid.generate_aldous_broder();
+
+        // The eight forms of the maze, its rotations and those of its mirror image
+        let mut forms = Vec::new();
+        for quarter_turns in 0..4 {
+            forms.push(grid.rotated(quarter_turns).unwrap());
+            forms.push(grid.mirrored().rotated(quarter_turns).unwrap());
+        }
+
+        let symmetries = vec![
+            grid.rotated(1).unwrap(),
+            grid.rotated(2).unwrap(),
+            grid.rotated(3).unwrap(),
+            grid.mirrored(),
+            grid.flipped(),
+            grid.transposed().unwrap(),
+            grid.flipped().transposed().unwrap()
+        ];
+
+        for symmetry in symmetries.iter() {
+            assert!(forms.contains(symmetry));
+            assert_eq!(symmetry.canonical_fingerprint(), grid.canonical_fingerprint());
+        }
+
+        // The canonical fingerprint is the fingerprint of one of the forms
+        assert!(forms.iter().any(|form| form.fingerprint() == grid.canonical_fingerprint()));
+
+        let distinct: HashSet<Grid<BaseCell>> = forms.into_iter().collect();
+        assert_eq!(distinct.len(), distinct.iter().map(|form| form.fingerprint()).collect::<HashSet<u64>>().len());
+    }
+
+    #[test]
+    fn test_canonical_tells_mazes_apart() {
+        // Rows hanging off the western column, and a path winding through the rows
+        let mut comb: Grid<BaseCell> = Grid::new(3, 3);
+        let mut snake: Grid<BaseCell> = Grid::new(3, 3);
+        for y in 0..3 {
+            comb.link_indices(0, y, 1, y);
+            comb.link_indices(1, y, 2, y);
+            snake.link_indices(0, y, 1, y);
+            snake.link_indices(1, y, 2, y);
+        }
+        comb.link_indices(0, 0, 0, 1);
+        comb.link_indices(0, 1, 0, 2);
+        snake.link_indices(2, 0, 2, 1);
+        snake.link_indices(0, 1, 0, 2);
+
+        assert!(comb != snake);
+        assert!(comb.canonical_fingerprint() != snake.canonical_fingerprint());
+        assert_eq!(snake.rotated(1).unwrap().canonical_fingerprint(), snake.canonical_fingerprint());
+    }
+}
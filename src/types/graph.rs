@@ -0,0 +1,384 @@
+extern crate serde_json;
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::maze::Maze;
+use super::topology::{Linkable, Topology};
+use super::super::error::MazeError;
+use super::super::output;
+
+/// Arbitrary undirected graph supplied by the caller, such as a room
+/// adjacency graph.
+///
+/// Nodes keep the ids they were given, ids used as cell ids are indices in
+/// the order the nodes were added. Nodes may have a position, which is only
+/// needed for rendering.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Graph {
+    ids: Vec<u64>,
+    index: HashMap<u64, usize>,
+    positions: Vec<Option<(f32, f32)>>,
+    boundary: Vec<bool>,
+    edges: Vec<Vec<usize>>
+}
+
+impl Graph {
+    pub fn new() -> Graph {
+        Graph {
+            ids: Vec::new(),
+            index: HashMap::new(),
+            positions: Vec::new(),
+            boundary: Vec::new(),
+            edges: Vec::new()
+        }
+    }
+
+    /// Parses a graph from a JSON document like
+    ///
+    /// ```text
+    /// {
+    ///     "nodes": [{"id": 1, "x": 0.0, "y": 0.0, "boundary": true}, {"id": 2}],
+    ///     "edges": [[1, 2]]
+    /// }
+    /// ```
+    ///
+    /// where positions and the boundary flag are optional. The graph has to
+    /// be connected, otherwise no spanning tree exists. `links` of a carved
+    /// graph are ignored, `Maze::<Graph>::from_json` reads them.
+    pub fn from_json(json: &str) -> Result<Graph, MazeError> {
+        let document: Value = match serde_json::from_str(json) {
+            Ok(document) => document,
+            Err(e) => return Err(invalid(format!("invalid JSON: {}", e)))
+        };
+
+        Graph::from_document(&document)
+    }
+
+    fn from_document(document: &Value) -> Result<Graph, MazeError> {
+        let mut graph = Graph::new();
+
+        let nodes = match document.as_object().and_then(|o| o.get("nodes")).and_then(|n| n.as_array()) {
+            Some(nodes) => nodes,
+            None => return Err(invalid("missing \"nodes\" array".to_string()))
+        };
+
+        for node in nodes.iter() {
+            let id = match node.as_object().and_then(|o| o.get("id")).and_then(|id| id.as_u64()) {
+                Some(id) => id,
+                None => return Err(invalid(format!("node without an integer \"id\": {:?}", node)))
+            };
+
+            if graph.index.contains_key(&id) {
+                return Err(invalid(format!("duplicate node {}", id)));
+            }
+
+            let object = node.as_object().unwrap();
+            let position = match (object.get("x").and_then(|x| x.as_f64()), object.get("y").and_then(|y| y.as_f64())) {
+                (Some(x), Some(y)) => Some((x as f32, y as f32)),
+                (None, None) => None,
+                _ => return Err(invalid(format!("node {} has only one coordinate", id)))
+            };
+
+            let index = graph.add_node(id, position);
+            let boundary = object.get("boundary").and_then(|b| b.as_bool()).unwrap_or(false);
+            graph.set_boundary(index, boundary);
+        }
+
+        let edges = match parse_pairs(document, "edges") {
+            Ok(Some(edges)) => edges,
+            Ok(None) => return Err(invalid("missing \"edges\" array".to_string())),
+            Err(e) => return Err(e)
+        };
+
+        for (id1, id2) in edges {
+            if let Err(e) = graph.add_edge(id1, id2) {
+                return Err(e);
+            }
+        }
+
+        if graph.size() == 0 {
+            return Err(invalid("graph has no nodes".to_string()));
+        }
+
+        if !graph.is_connected() {
+            return Err(invalid("graph is not connected".to_string()));
+        }
+
+        Ok(graph)
+    }
+
+    /// Connects two nodes given by their ids.
+    pub fn add_edge(&mut self, id1: u64, id2: u64) -> Result<(), MazeError> {
+        let (index1, index2) = match (self.index_of(id1), self.index_of(id2)) {
+            (Some(index1), Some(index2)) => (index1, index2),
+            (None, _) => return Err(invalid(format!("edge to unknown node {}", id1))),
+            (_, None) => return Err(invalid(format!("edge to unknown node {}", id2)))
+        };
+
+        if index1 == index2 {
+            return Err(invalid(format!("edge from node {} to itself", id1)));
+        }
+
+        if !self.edges[index1].contains(&index2) {
+            self.edges[index1].push(index2);
+            self.edges[index2].push(index1);
+        }
+
+        Ok(())
+    }
+
+    /// Adds a node and returns its index.
+    pub fn add_node(&mut self, id: u64, position: Option<(f32, f32)>) -> usize {
+        let index = self.ids.len();
+        self.ids.push(id);
+        self.index.insert(id, index);
+        self.positions.push(position);
+        self.boundary.push(false);
+        self.edges.push(Vec::new());
+        index
+    }
+
+    /// Smallest and largest coordinates of the node positions.
+    pub fn bounds(&self) -> ((f32, f32), (f32, f32)) {
+        let mut positions = self.positions.iter().filter_map(|p| *p);
+        let first = match positions.next() {
+            Some(first) => first,
+            None => return ((0.0, 0.0), (0.0, 0.0))
+        };
+
+        positions.fold((first, first), |(min, max), p| {
+            ((min.0.min(p.0), min.1.min(p.1)), (max.0.max(p.0), max.1.max(p.1)))
+        })
+    }
+
+    /// All edges, each listed once with the lower index first.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut res = Vec::new();
+        for (index, neighbors) in self.edges.iter().enumerate() {
+            for &neighbor in neighbors.iter() {
+                if index < neighbor {
+                    res.push((index, neighbor));
+                }
+            }
+        }
+
+        return res;
+    }
+
+    /// Whether every node has a position, so the graph can be drawn.
+    pub fn has_positions(&self) -> bool {
+        self.positions.iter().all(|p| p.is_some())
+    }
+
+    /// Id the node at the given index was added with.
+    pub fn id_of(&self, index: usize) -> u64 {
+        self.ids[index]
+    }
+
+    pub fn index_of(&self, id: u64) -> Option<usize> {
+        self.index.get(&id).cloned()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        if self.ids.is_empty() {
+            return true;
+        }
+
+        let mut visited = vec![false; self.ids.len()];
+        let mut stack = vec![0];
+        visited[0] = true;
+        while let Some(index) = stack.pop() {
+            for &next in self.edges[index].iter() {
+                if !visited[next] {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        visited.iter().all(|&v| v)
+    }
+
+    pub fn position(&self, index: usize) -> Option<(f32, f32)> {
+        self.positions[index]
+    }
+
+    /// Marks the node as lying on the outside of the maze, where an
+    /// entrance or exit can be placed.
+    pub fn set_boundary(&mut self, index: usize, boundary: bool) {
+        self.boundary[index] = boundary;
+    }
+}
+
+fn invalid(reason: String) -> MazeError {
+    MazeError::Input(reason)
+}
+
+/// Pairs of node ids listed under `key`, `None` if the document has none.
+fn parse_pairs(document: &Value, key: &str) -> Result<Option<Vec<(u64, u64)>>, MazeError> {
+    let values = match document.as_object().and_then(|o| o.get(key)).map(|values| values.as_array()) {
+        Some(Some(values)) => values,
+        Some(None) => return Err(invalid(format!("\"{}\" is not an array", key))),
+        None => return Ok(None)
+    };
+
+    let mut res = Vec::new();
+    for value in values.iter() {
+        let pair: Vec<u64> = match value.as_array() {
+            Some(pair) if pair.len() == 2 => pair.iter().filter_map(|id| id.as_u64()).collect(),
+            _ => Vec::new()
+        };
+
+        if pair.len() != 2 {
+            return Err(invalid(format!("{} is not a pair of node ids: {:?}", key, value)));
+        }
+
+        res.push((pair[0], pair[1]));
+    }
+
+    Ok(Some(res))
+}
+
+impl Topology for Graph {
+    fn size(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        self.edges[id].clone()
+    }
+
+    fn is_boundary_id(&self, id: usize) -> bool {
+        self.boundary[id]
+    }
+}
+
+impl Maze<Graph> {
+    /// Reads back a maze written by `to_json`: the graph as accepted by
+    /// `Graph::from_json`, and the `links` which were carved, if any. Every
+    /// link has to be one of the edges.
+    pub fn from_json(json: &str) -> Result<Maze<Graph>, MazeError> {
+        let document: Value = match serde_json::from_str(json) {
+            Ok(document) => document,
+            Err(e) => return Err(invalid(format!("invalid JSON: {}", e)))
+        };
+
+        let graph = match Graph::from_document(&document) {
+            Ok(graph) => graph,
+            Err(e) => return Err(e)
+        };
+
+        let links = match parse_pairs(&document, "links") {
+            Ok(links) => links.unwrap_or_else(Vec::new),
+            Err(e) => return Err(e)
+        };
+
+        let mut maze = Maze::new(graph);
+        for (id1, id2) in links {
+            let pair = (maze.shape().index_of(id1), maze.shape().index_of(id2));
+            match pair {
+                (Some(index1), Some(index2)) if maze.shape().neighbor_ids(index1).contains(&index2) => maze.link_ids(index1, index2),
+                _ => return Err(invalid(format!("link between nodes {} and {} is not an edge", id1, id2)))
+            }
+        }
+
+        Ok(maze)
+    }
+
+    pub fn print_json(&self) {
+        println!("{}", self.to_json());
+    }
+
+    pub fn to_json(&self) -> String {
+        output::json_graph::format(self)
+    }
+
+    /// Draws passages as corridors between node positions, so nodes one
+    /// unit apart are `cell_size` pixels apart. All nodes need a position.
+    pub fn to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+        self.try_to_png(cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+    }
+
+    /// Same as `to_png`, failing on nodes without a position or when the
+    /// image can't be written.
+    pub fn try_to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+        output::png_graph::try_format(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+
+    pub fn to_svg(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3]) -> String {
+        output::svg_graph::format(self, cell_size, wall_size, color_cell, color_wall)
+    }
+
+    /// Same as `to_svg`, failing on nodes without a position.
+    pub fn try_to_svg(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3]) -> Result<String, MazeError> {
+        output::svg_graph::try_format(self, cell_size, wall_size, color_cell, color_wall)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::maze::Maze;
+    use super::super::topology::{Linkable, Topology};
+
+    const SQUARE: &'static str = r#"{
+        "nodes": [{"id": 10, "x": 0, "y": 0}, {"id": 20, "x": 1, "y": 0}, {"id": 30, "x": 1, "y": 1}, {"id": 40, "x": 0, "y": 1}],
+        "edges": [[10, 20], [20, 30], [30, 40], [40, 10]]
+    }"#;
+
+    #[test]
+    fn test_from_json() {
+        let graph = Graph::from_json(SQUARE).unwrap();
+        assert_eq!(graph.size(), 4);
+        assert!(graph.has_positions());
+        assert_eq!(graph.id_of(2), 30);
+        assert_eq!(graph.position(2), Some((1.0, 1.0)));
+        assert_eq!(graph.neighbor_ids(0), vec![1, 3]);
+        assert_eq!(graph.edges().len(), 4);
+    }
+
+    #[test]
+    fn test_from_json_rejects_bad_graphs() {
+        assert!(Graph::from_json(r#"{"nodes": [{"id": 1}], "edges": [[1, 2]]}"#).is_err());
+        assert!(Graph::from_json(r#"{"nodes": [{"id": 1}, {"id": 2}], "edges": []}"#).is_err());
+        assert!(Graph::from_json(r#"{"nodes": [{"id": 1}, {"id": 1}], "edges": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_maze_from_json() {
+        let mut maze = Maze::new(Graph::from_json(SQUARE).unwrap());
+        maze.generate_wilson();
+
+        let read = Maze::<Graph>::from_json(&maze.to_json()[..]).unwrap();
+        assert_eq!(read.size(), maze.size());
+        assert_eq!(read.shape().edges().len(), maze.shape().edges().len());
+        assert_eq!(read.links, maze.links);
+
+        let not_an_edge = r#"{"nodes": [{"id": 1}, {"id": 2}, {"id": 3}], "edges": [[1, 2], [2, 3]], "links": [[1, 3]]}"#;
+        assert!(match Maze::<Graph>::from_json(not_an_edge) {
+            Err(MazeError::Input(_)) => true,
+            _ => false
+        });
+    }
+
+    #[test]
+    fn test_drawing_needs_positions() {
+        let maze = Maze::new(Graph::from_json(r#"{"nodes": [{"id": 1}, {"id": 2}], "edges": [[1, 2]]}"#).unwrap());
+        assert!(maze.try_to_svg(10, 2, &[255, 255, 255], &[0, 0, 0]).is_err());
+        assert!(maze.try_to_png(10, 2, &[255, 255, 255], &[0, 0, 0], "graph.png").is_err());
+
+        let maze = Maze::new(Graph::from_json(SQUARE).unwrap());
+        assert!(maze.try_to_svg(10, 2, &[255, 255, 255], &[0, 0, 0]).is_ok());
+    }
+
+    #[test]
+    fn test_spanning_tree() {
+        let mut maze = Maze::new(Graph::from_json(SQUARE).unwrap());
+        maze.generate_aldous_broder();
+
+        let links: usize = (0..maze.size()).map(|id| maze.linked_ids(id).len()).sum();
+        assert_eq!(links / 2, maze.size() - 1);
+    }
+}
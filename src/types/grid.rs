@@ -1,34 +1,91 @@
 extern crate rand;
 
-use std::collections::BTreeSet;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
 use std::ops::{Index, IndexMut};
 
+use rand::{SeedableRng, StdRng};
 use rand::distributions::{IndependentSample, Range};
 
 use super::cell::Cell;
+use super::fingerprint;
+use super::links::Links;
+use super::opening::Opening;
+use super::rectangle::{Direction, DIRECTIONS, Rectangle, Wrap};
+use super::topology::{Linkable, Topology};
+use super::super::error::MazeError;
 use super::super::generator;
 use super::super::output;
+use super::super::placement;
+use super::super::transform;
+use super::super::validation;
+
+/// Key of a link in `Grid.link_weights`, the same both ways.
+fn link_key(a: (usize, usize), b: (usize, usize)) -> ((usize, usize), (usize, usize)) {
+    match a < b {
+        true => (a, b),
+        false => (b, a)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Grid<T>
     where T: Cell + Clone
 {
-    x: usize,
-    y: usize,
+    shape: Rectangle,
     pub cells: Vec<Vec<T>>,
-    pub links: HashMap<(usize, usize), BTreeSet<(usize, usize)>>
+    pub links: Links,
+    entrance: Option<Opening>,
+    exit: Option<Opening>,
+    /// Cost of entering a cell, where it isn't 1
+    weights: HashMap<(usize, usize), usize>,
+    /// Cost of passing a link, in both directions, overriding the weight of
+    /// the cell entered. Keyed by the lower cell first.
+    link_weights: HashMap<((usize, usize), (usize, usize)), usize>,
+    /// Name of the generator which carved the maze, as accepted by `--algorithm`
+    algorithm: Option<String>,
+    /// Seed the maze was carved from, when it can be carved again
+    seed: Option<usize>,
+    /// Cells left out of the maze by the caller, never linked
+    mask: BTreeSet<(usize, usize)>,
+    /// Data attached to cells by the caller, such as items or room names
+    metadata: HashMap<(usize, usize), BTreeMap<String, String>>
 }
 
 impl <T> Grid<T>
     where T: Cell + Clone
 {
+    /// Grid of `x * y` cells. Generators and outputs need at least one
+    /// cell, `try_new` checks for that.
     pub fn new(x: usize, y: usize) -> Grid<T> {
+        Grid::with_wrap(x, y, Wrap::None)
+    }
+
+    pub fn try_new(x: usize, y: usize) -> Result<Grid<T>, MazeError> {
+        Grid::try_with_wrap(x, y, Wrap::None)
+    }
+
+    /// Same as `with_wrap`, failing on grids without any cells.
+    pub fn try_with_wrap(x: usize, y: usize, wrap: Wrap) -> Result<Grid<T>, MazeError> {
+        match x == 0 || y == 0 {
+            true => Err(MazeError::EmptyGrid(x, y)),
+            false => Ok(Grid::with_wrap(x, y, wrap))
+        }
+    }
+
+    pub fn with_wrap(x: usize, y: usize, wrap: Wrap) -> Grid<T> {
         let mut grid = Grid {
-            x: x,
-            y: y,
+            shape: Rectangle::with_wrap(x, y, wrap),
             cells: Vec::with_capacity(x),
-            links: HashMap::new()
+            links: Links::new(Rectangle::with_wrap(x, y, wrap)),
+            entrance: None,
+            exit: None,
+            weights: HashMap::new(),
+            link_weights: HashMap::new(),
+            algorithm: None,
+            seed: None,
+            mask: BTreeSet::new(),
+            metadata: HashMap::new()
         };
 
         for i in 0..x {
@@ -43,6 +100,18 @@ impl <T> Grid<T>
         return grid;
     }
 
+    /// Maze written by `to_json`, failing on coordinates outside of the grid
+    /// and on links which don't have a way back and aren't one-way.
+    pub fn from_json(json: &str) -> Result<Grid<T>, MazeError> {
+        output::json::parse(json)
+    }
+
+    /// Same as `from_json`, failing only on coordinates outside of the grid,
+    /// so a broken maze can be loaded to be validated.
+    pub fn from_json_lenient(json: &str) -> Result<Grid<T>, MazeError> {
+        output::json::parse_lenient(json)
+    }
+
     pub fn print_ascii(&self) {
         print!("{}", self.to_string());
     }
@@ -51,38 +120,181 @@ impl <T> Grid<T>
         println!("{}", self.to_json());
     }
 
+    /// Fingerprint of the size, wrap and links of the maze, the same on
+    /// every run and with every version of Rust.
+    pub fn fingerprint(&self) -> u64 {
+        fingerprint::fingerprint(self)
+    }
+
+    /// Grid flipped upside down.
+    pub fn flipped(&self) -> Grid<T> {
+        transform::map::flip(self)
+    }
+
     pub fn generate_aldous_broder(&mut self) {
+        self.algorithm = Some("aldous-broder".to_string());
         generator::aldous_broder::generate(self)
     }
 
+    /// Same as `generate_aldous_broder`, always carving the same maze for
+    /// the same seed and size.
+    pub fn generate_aldous_broder_seeded(&mut self, seed: usize) {
+        let mut rng: StdRng = SeedableRng::from_seed(&[seed][..]);
+        self.algorithm = Some("aldous-broder".to_string());
+        self.seed = Some(seed);
+        generator::aldous_broder::generate_with_rng(self, &mut rng)
+    }
+
     pub fn generate_binary(&mut self) {
+        self.algorithm = Some("binary".to_string());
         generator::binary::generate(self)
     }
 
     pub fn generate_sidewinder(&mut self) {
+        self.algorithm = Some("sidewinder".to_string());
         generator::sidewinder::generate(self)
     }
 
     pub fn generate_wilson(&mut self) {
+        self.algorithm = Some("wilson".to_string());
         generator::wilson::generate(self)
     }
 
+    /// Name of the generator which carved the maze, if it is known.
+    pub fn algorithm(&self) -> Option<&str> {
+        self.algorithm.as_ref().map(|algorithm| &algorithm[..])
+    }
+
+    /// Side of the outer wall an opening in cell (x, y) would go through,
+    /// or `None` for interior cells.
+    pub fn boundary_side(&self, x: usize, y: usize) -> Option<Direction> {
+        let wraps_x = self.shape.wrap().wraps_x();
+        let wraps_y = self.shape.wrap().wraps_y();
+
+        if x == 0 && !wraps_x {
+            Some(Direction::West)
+        } else if x == self.x() - 1 && !wraps_x {
+            Some(Direction::East)
+        } else if y == 0 && !wraps_y {
+            Some(Direction::North)
+        } else if y == self.y() - 1 && !wraps_y {
+            Some(Direction::South)
+        } else {
+            None
+        }
+    }
+
+    /// Cell at (x, y), failing instead of panicking like indexing does
+    /// when it is outside of the grid.
+    pub fn cell(&self, x: usize, y: usize) -> Result<&T, MazeError> {
+        match self.check_indices(x, y) {
+            Ok(()) => Ok(&self.cells[x][y]),
+            Err(e) => Err(e)
+        }
+    }
+
+    pub fn check_indices(&self, x: usize, y: usize) -> Result<(), MazeError> {
+        match x < self.x() && y < self.y() {
+            true => Ok(()),
+            false => Err(MazeError::OutOfBounds(x, y))
+        }
+    }
+
+    /// Fingerprint which is the same for this maze, its rotations and its
+    /// mirror images, for telling apart mazes which aren't just turned.
+    pub fn canonical_fingerprint(&self) -> u64 {
+        fingerprint::canonical(self)
+    }
+
+    /// Grid with this one to the west and `other` to the east, joined by
+    /// `openings` passages. Both need the same height.
+    pub fn concat_east(&self, other: &Grid<T>, openings: usize) -> Result<Grid<T>, MazeError> {
+        transform::tile::tile(&[vec![self.clone(), other.clone()]], openings)
+    }
+
+    /// Grid with this one to the north and `other` to the south, joined by
+    /// `openings` passages. Both need the same width.
+    pub fn concat_south(&self, other: &Grid<T>, openings: usize) -> Result<Grid<T>, MazeError> {
+        transform::tile::tile(&[vec![self.clone()], vec![other.clone()]], openings)
+    }
+
+    /// Part of the grid with its top left cell at (x, y).
+    pub fn cropped(&self, x: usize, y: usize, width: usize, height: usize) -> Result<Grid<T>, MazeError> {
+        transform::map::crop(self, x, y, width, height)
+    }
+
+    /// Cost of moving from cell (x1, y1) to (x2, y2), the weight of the
+    /// link if it has one, otherwise the weight of the cell entered.
+    pub fn cost_indices(&self, x1: usize, y1: usize, x2: usize, y2: usize) -> usize {
+        match self.link_weight(x1, y1, x2, y2) {
+            Some(weight) => weight,
+            None => self.weight(x2, y2)
+        }
+    }
+
+    pub fn entrance(&self) -> Option<Opening> {
+        self.entrance
+    }
+
+    pub fn exit(&self) -> Option<Opening> {
+        self.exit
+    }
+
+    pub fn is_boundary_indices(&self, x: usize, y: usize) -> bool {
+        !self.is_masked(x, y) && self.shape.is_boundary(x, y)
+    }
+
+    /// Whether there is no wall on the given side of the cell, a passage
+    /// in either direction. The outer wall is never open, use `is_opening`
+    /// for the entrance and exit.
+    pub fn is_open(&self, cell: (usize, usize), direction: Direction) -> bool {
+        match self.neighbor(cell, direction) {
+            Some((nx, ny)) => self.is_open_indices(cell.0, cell.1, nx, ny),
+            None => false
+        }
+    }
+
+    pub fn is_opening(&self, x: usize, y: usize, side: Direction) -> bool {
+        let opening = Opening::new(x, y, side);
+        self.entrance == Some(opening) || self.exit == Some(opening)
+    }
+
     pub fn is_linked_indices(&self, x1: usize, y1: usize, x2: usize, y2: usize) -> bool {
-        match self.links.get(&(x1, y1)) {
-            Some(set) => {
-                match set.get(&(x2, y2)) {
-                    Some(_) => {
-                        true
-                    },
-                    _ => {
-                        false
-                    }
-                }
-            },
+        self.links.contains((x1, y1), (x2, y2))
+    }
+
+    /// Whether cell (x, y) is linked to the given neighbour, as returned by
+    /// `Rectangle::east` and friends. `None` is never linked.
+    pub fn is_linked_neighbor(&self, x: usize, y: usize, neighbor: Option<(usize, usize)>) -> bool {
+        match neighbor {
+            Some((nx, ny)) => self.is_linked_indices(x, y, nx, ny),
             None => false
         }
     }
 
+    /// One-way passage from cell (x1, y1) to (x2, y2), which can't be passed
+    /// the other way.
+    pub fn is_one_way_indices(&self, x1: usize, y1: usize, x2: usize, y2: usize) -> bool {
+        self.is_linked_indices(x1, y1, x2, y2) && !self.is_linked_indices(x2, y2, x1, y1)
+    }
+
+    /// Whether there is a passage between the cells in either direction,
+    /// so there is no wall between them.
+    pub fn is_open_indices(&self, x1: usize, y1: usize, x2: usize, y2: usize) -> bool {
+        self.is_linked_indices(x1, y1, x2, y2) || self.is_linked_indices(x2, y2, x1, y1)
+    }
+
+    /// Whether the cell was left out of the maze by `set_masked`.
+    pub fn is_masked(&self, x: usize, y: usize) -> bool {
+        self.mask.contains(&(x, y))
+    }
+
+    /// Whether every cell can be reached from every other by exactly one
+    /// path, `validate` tells what is wrong otherwise.
+    pub fn is_perfect(&self) -> bool {
+        self.validate().is_perfect()
+    }
+
     pub fn is_linked(&self, cell1: &T, cell2: &T) -> bool {
         self.is_linked_indices(cell1.x(), cell1.y(), cell2.x(), cell2.y())
     }
@@ -91,79 +303,118 @@ impl <T> Grid<T>
         self.link_indices(cell1.x(), cell1.y(), cell2.x(), cell2.y());
     }
 
+    /// Two-way passage between the cells.
     pub fn link_indices(&mut self, x1: usize, y1: usize, x2: usize, y2: usize) {
+        self.links.insert_both((x1, y1), (x2, y2));
+    }
+
+    pub fn link_weight(&self, x1: usize, y1: usize, x2: usize, y2: usize) -> Option<usize> {
+        self.link_weights.get(&link_key((x1, y1), (x2, y2))).cloned()
+    }
+
+    /// Links with a cost of their own, lower cell first, in order.
+    pub fn link_weights(&self) -> Vec<(((usize, usize), (usize, usize)), usize)> {
+        let mut res: Vec<(((usize, usize), (usize, usize)), usize)> = self.link_weights.iter().map(|(&link, &weight)| (link, weight)).collect();
+        res.sort();
+        res
+    }
+
+    /// Turns the passage between the cells into a one-way passage from cell
+    /// (x1, y1) to (x2, y2), such as a one-way door or a slide.
+    pub fn link_one_way_indices(&mut self, x1: usize, y1: usize, x2: usize, y2: usize) {
+        self.unlink_pair(x2, y2, x1, y1);
         self.link_pair(x1, y1, x2, y2);
-        self.link_pair(x2, y2, x1, y1);
     }
 
+    /// Adds the direction from cell (x1, y1) to (x2, y2) to the passage
+    /// between them, leaving the other direction as it is. Links are stored
+    /// per direction, a two-way passage is a pair of them.
     pub fn link_pair(&mut self, x1: usize, y1: usize, x2: usize, y2: usize) {
-        match self.links.contains_key(&(x1, y1)) {
-            true => {
-                self.links.get_mut(&(x1, y1)).unwrap().insert((x2, y2));
-            },
-            false => {
-                let mut set: BTreeSet<(usize, usize)> = BTreeSet::new();
-                set.insert((x2, y2));
-                self.links.insert((x1, y1), set);
-            }
-        }
+        self.links.insert((x1, y1), (x2, y2));
     }
 
-    pub fn neighbors(&self, cell: &T) -> Vec<T> {
-        self.neighbors_indices(cell.x(), cell.y())
+    /// Highest weight of any cell.
+    pub fn max_weight(&self) -> usize {
+        self.weights.values().fold(1, |max, &weight| max.max(weight))
     }
 
-    pub fn neighbors_indices(&self, x: usize, y: usize) -> Vec<T> {
-        let mut res = Vec::new();
+    /// Cells left out of the maze, in order.
+    pub fn mask(&self) -> Vec<(usize, usize)> {
+        self.mask.iter().cloned().collect()
+    }
 
-        if x > 0 {
-            res.push(self.cells[x - 1][y].clone());
-        }
+    /// Data attached to the cell by `set_metadata`.
+    pub fn metadata(&self, x: usize, y: usize) -> Option<&BTreeMap<String, String>> {
+        self.metadata.get(&(x, y))
+    }
 
-        if x < self.x - 1 {
-            res.push(self.cells[x + 1][y].clone());
-        }
+    /// Grid mirrored left to right.
+    pub fn mirrored(&self) -> Grid<T> {
+        transform::map::mirror(self)
+    }
 
-        if y > 0 {
-            res.push(self.cells[x][y - 1].clone());
+    /// Neighbour of the cell in the given direction, across a joined edge
+    /// of wrapped grids, `None` on the outer wall and for masked cells.
+    pub fn neighbor(&self, cell: (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        match self.is_masked(cell.0, cell.1) {
+            true => None,
+            false => self.shape.neighbor(cell.0, cell.1, direction).and_then(|(x, y)| match self.is_masked(x, y) {
+                true => None,
+                false => Some((x, y))
+            })
         }
+    }
 
-        if y < self.y - 1 {
-            res.push(self.cells[x][y + 1].clone());
-        }
+    pub fn neighbors(&self, cell: &T) -> Vec<T> {
+        self.neighbors_indices(cell.x(), cell.y())
+    }
 
-        return res;
+    pub fn neighbors_indices(&self, x: usize, y: usize) -> Vec<T> {
+        self.shape.neighbors(x, y).iter().map(|&(nx, ny)| self.cells[nx][ny].clone()).collect()
     }
 
     pub fn neighbors_linked(&self, cell: &T) -> Vec<T> {
         self.neighbors_linked_indices(cell.x(), cell.y())
     }
 
+    /// Cells linked from cell (x, y), including the other end of portals.
     pub fn neighbors_linked_indices(&self, x: usize, y: usize) -> Vec<T> {
-        let mut res = Vec::new();
+        self.links.from((x, y)).iter().map(|&(nx, ny)| self.cells[nx][ny].clone()).collect()
+    }
 
-        if x > 0 && self.is_linked_indices(x, y, x - 1, y) {
-            res.push(self.cells[x - 1][y].clone());
-        }
+    /// Sides of the cell without a wall, in the order of `DIRECTIONS`.
+    pub fn open_directions(&self, cell: (usize, usize)) -> Vec<Direction> {
+        DIRECTIONS.iter().cloned().filter(|&direction| self.is_open(cell, direction)).collect()
+    }
 
-        if x < self.x - 1 && self.is_linked_indices(x, y, x + 1, y) {
-            res.push(self.cells[x + 1][y].clone());
-        }
+    pub fn place_corners(&mut self) {
+        self.try_place_corners().unwrap()
+    }
 
-        if y > 0 && self.is_linked_indices(x, y, x, y - 1) {
-            res.push(self.cells[x][y - 1].clone());
-        }
+    pub fn place_longest_path(&mut self) {
+        placement::longest_path::place(self)
+    }
 
-        if y < self.y - 1 && self.is_linked_indices(x, y, x, y + 1){
-            res.push(self.cells[x][y + 1].clone());
-        }
+    pub fn place_random(&mut self) {
+        placement::random::place(self)
+    }
+
+    pub fn place_portals(&mut self, count: usize) {
+        placement::portals::place(self, count)
+    }
 
-        return res;
+    /// Links between cells which aren't adjacent, each listed once with the
+    /// lower cell first, in order.
+    pub fn portals(&self) -> Vec<((usize, usize), (usize, usize))> {
+        self.links.pairs().into_iter().filter(|&(from, to)| {
+            !self.shape.neighbors(from.0, from.1).contains(&to) &&
+                (from < to || !self.is_linked_indices(to.0, to.1, from.0, from.1))
+        }).collect()
     }
 
     pub fn random_cell(&self) -> T {
-        let between_x = Range::new(0, self.x);
-        let between_y = Range::new(0, self.y);
+        let between_x = Range::new(0, self.x());
+        let between_y = Range::new(0, self.y());
         let mut rng = rand::thread_rng();
 
         let x = between_x.ind_sample(&mut rng);
@@ -172,22 +423,180 @@ impl <T> Grid<T>
         self.cells[x][y].clone()
     }
 
+    /// Sets the name of the generator, for mazes carved outside of the grid.
+    pub fn set_algorithm(&mut self, algorithm: Option<String>) {
+        self.algorithm = algorithm;
+    }
+
+    pub fn set_entrance(&mut self, entrance: Option<Opening>) {
+        self.entrance = entrance;
+    }
+
+    pub fn set_exit(&mut self, exit: Option<Opening>) {
+        self.exit = exit;
+    }
+
+    /// Sets the cost of passing between two cells, whichever way.
+    pub fn set_link_weight(&mut self, x1: usize, y1: usize, x2: usize, y2: usize, weight: usize) {
+        self.try_set_link_weight(x1, y1, x2, y2, weight).unwrap()
+    }
+
+    /// Leaves the cell out of the maze, removing its links and their
+    /// weights, or puts it back. Generators carve around masked cells.
+    pub fn set_masked(&mut self, x: usize, y: usize, masked: bool) {
+        if !masked {
+            self.mask.remove(&(x, y));
+            return;
+        }
+
+        self.mask.insert((x, y));
+        self.links.remove_cell((x, y));
+
+        let touching: Vec<((usize, usize), (usize, usize))> = self.link_weights.keys()
+            .filter(|&&(a, b)| a == (x, y) || b == (x, y))
+            .cloned()
+            .collect();
+        for key in touching {
+            self.link_weights.remove(&key);
+        }
+    }
+
+    /// Attaches a value to the cell, written to and read from JSON along
+    /// with the maze.
+    pub fn set_metadata(&mut self, x: usize, y: usize, key: &str, value: &str) {
+        self.metadata.entry((x, y)).or_insert_with(BTreeMap::new).insert(key.to_string(), value.to_string());
+    }
+
+    pub fn set_seed(&mut self, seed: Option<usize>) {
+        self.seed = seed;
+    }
+
+    /// Sets the cost of entering a cell, such as a swamp or stairs.
+    pub fn set_weight(&mut self, x: usize, y: usize, weight: usize) {
+        self.try_set_weight(x, y, weight).unwrap()
+    }
+
+    /// Grid rotated clockwise by `quarter_turns` times 90 degrees, so
+    /// rotating by 1 turns a landscape maze into a portrait one.
+    pub fn rotated(&self, quarter_turns: usize) -> Result<Grid<T>, MazeError> {
+        transform::map::rotate(self, quarter_turns)
+    }
+
+    /// Seed the maze was carved from, if it is known.
+    pub fn seed(&self) -> Option<usize> {
+        self.seed
+    }
+
+    pub fn shape(&self) -> &Rectangle {
+        &self.shape
+    }
+
     pub fn size(&self) -> usize {
-        self.x * self.y
+        self.shape.size()
+    }
+
+    /// `columns x rows` copies of the grid, neighbouring copies joined by
+    /// `openings` passages.
+    pub fn tiled(&self, columns: usize, rows: usize, openings: usize) -> Result<Grid<T>, MazeError> {
+        transform::tile::tile(&vec![vec![self.clone(); columns]; rows], openings)
     }
 
     pub fn to_json(&self) -> String {
         output::json::format(self)
     }
 
+    /// Same as `to_json`, with the shortest path from the entrance to the
+    /// exit.
+    pub fn to_json_with_solution(&self) -> String {
+        output::json::format_with_solution(self)
+    }
+
     pub fn to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
         output::png::format(self, cell_size, wall_size, color_cell, color_wall, output_filename)
     }
 
+    /// Same as `to_png`, with cells tinted towards the wall color by weight.
+    pub fn to_png_weights(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+        output::png::format_weights(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+
     pub fn to_string(&self) -> String {
         output::ascii::format(self)
     }
 
+    /// Same as `to_string`, with every cell labelled by `label`, which
+    /// should return three characters.
+    pub fn to_string_with<F>(&self, label: F) -> String
+        where F: Fn(&T) -> String
+    {
+        output::ascii::format_with(self, label)
+    }
+
+    /// Grid mirrored along its diagonal from the top left corner.
+    pub fn transposed(&self) -> Result<Grid<T>, MazeError> {
+        transform::map::transpose(self)
+    }
+
+    /// Same as `link_indices`, failing when either cell is outside of the
+    /// grid.
+    pub fn try_link_indices(&mut self, x1: usize, y1: usize, x2: usize, y2: usize) -> Result<(), MazeError> {
+        match self.check_indices(x1, y1).and(self.check_indices(x2, y2)) {
+            Ok(()) => {
+                self.link_indices(x1, y1, x2, y2);
+                Ok(())
+            },
+            Err(e) => Err(e)
+        }
+    }
+
+    /// Same as `place_corners`, failing on empty grids.
+    pub fn try_place_corners(&mut self) -> Result<(), MazeError> {
+        placement::corners::place(self)
+    }
+
+    /// Same as `set_link_weight`, failing on cells outside of the grid and
+    /// on a weight of 0.
+    pub fn try_set_link_weight(&mut self, x1: usize, y1: usize, x2: usize, y2: usize, weight: usize) -> Result<(), MazeError> {
+        if let Err(e) = self.check_indices(x1, y1).and(self.check_indices(x2, y2)) {
+            return Err(e);
+        }
+
+        if weight == 0 {
+            return Err(MazeError::ZeroWeight);
+        }
+
+        self.link_weights.insert(link_key((x1, y1), (x2, y2)), weight);
+        Ok(())
+    }
+
+    /// Same as `set_weight`, failing on cells outside of the grid and on a
+    /// weight of 0.
+    pub fn try_set_weight(&mut self, x: usize, y: usize, weight: usize) -> Result<(), MazeError> {
+        if let Err(e) = self.check_indices(x, y) {
+            return Err(e);
+        }
+
+        if weight == 0 {
+            return Err(MazeError::ZeroWeight);
+        }
+
+        match weight {
+            1 => self.weights.remove(&(x, y)),
+            _ => self.weights.insert((x, y), weight)
+        };
+        Ok(())
+    }
+
+    /// Same as `to_png`, failing when the image can't be written.
+    pub fn try_to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+        output::png::try_format(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+
+    /// Same as `to_png_weights`, failing when the image can't be written.
+    pub fn try_to_png_weights(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+        output::png::try_format_weights(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+
     pub fn unlink(&mut self, cell1: &T, cell2: &T) {
         self.unlink_indices(cell1.x(), cell1.y(), cell2.x(), cell2.y());
     }
@@ -197,42 +606,132 @@ impl <T> Grid<T>
         self.unlink_pair(x2, y2, x1, y1);
     }
 
+    /// Removes the direction from cell (x1, y1) to (x2, y2) only.
     pub fn unlink_pair(&mut self, x1: usize, y1: usize, x2: usize, y2: usize) {
-        let mut remove = false;
-        match self.links.contains_key(&(x1, y1)) {
-            true => {
-                let set = self.links.get_mut(&(x1, y1)).unwrap();
-                set.remove(&(x2, y2));
-                if set.is_empty() {
-                    remove = true;
-                }
-            },
-            _ => {}
-        }
+        self.links.remove((x1, y1), (x2, y2));
+    }
 
-        if remove {
-            self.links.remove(&(x1, y1));
-        }
+    /// Connected components, unreachable cells, cycles and odd links of the
+    /// maze.
+    pub fn validate(&self) -> validation::report::Report {
+        validation::report::validate(self)
     }
 
     pub fn visit<F>(&mut self, mut f: F)
         where F: FnMut(&mut Grid<T>, &T)
     {
         let mut grid = self;
-        for x in 0..grid.x {
-            for y in 0..grid.y {
+        for x in 0..grid.x() {
+            for y in 0..grid.y() {
                 let cell = grid[x][y].clone();
                 f(grid, &cell);
             }
         }
     }
 
+    /// Cost of entering the cell, 1 unless set otherwise.
+    pub fn weight(&self, x: usize, y: usize) -> usize {
+        match self.weights.get(&(x, y)) {
+            Some(&weight) => weight,
+            None => 1
+        }
+    }
+
     pub fn x(&self) -> usize {
-        self.x
+        self.shape.x()
     }
 
     pub fn y(&self) -> usize {
-        self.y
+        self.shape.y()
+    }
+}
+
+impl <T> Topology for Grid<T>
+    where T: Cell + Clone
+{
+    fn size(&self) -> usize {
+        self.shape.size()
+    }
+
+    /// Neighbours which aren't masked, none for masked cells.
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        let (x, y) = self.shape.coords(id);
+        if self.is_masked(x, y) {
+            return Vec::new();
+        }
+
+        self.shape.neighbor_ids(id).into_iter().filter(|&neighbor| {
+            let (nx, ny) = self.shape.coords(neighbor);
+            !self.is_masked(nx, ny)
+        }).collect()
+    }
+
+    /// Masked cells aren't part of the maze, so they aren't on its boundary
+    /// either.
+    fn is_boundary_id(&self, id: usize) -> bool {
+        let (x, y) = self.shape.coords(id);
+        !self.is_masked(x, y) && self.shape.is_boundary_id(id)
+    }
+}
+
+impl <T> Linkable for Grid<T>
+    where T: Cell + Clone
+{
+    fn cost_ids(&self, from: usize, to: usize) -> usize {
+        let (x1, y1) = self.shape.coords(from);
+        let (x2, y2) = self.shape.coords(to);
+        self.cost_indices(x1, y1, x2, y2)
+    }
+
+    fn is_linked_ids(&self, id1: usize, id2: usize) -> bool {
+        let (x1, y1) = self.shape.coords(id1);
+        let (x2, y2) = self.shape.coords(id2);
+        self.is_linked_indices(x1, y1, x2, y2)
+    }
+
+    fn link_ids(&mut self, id1: usize, id2: usize) {
+        let (x1, y1) = self.shape.coords(id1);
+        let (x2, y2) = self.shape.coords(id2);
+        self.link_indices(x1, y1, x2, y2)
+    }
+
+    fn unlink_ids(&mut self, id1: usize, id2: usize) {
+        let (x1, y1) = self.shape.coords(id1);
+        let (x2, y2) = self.shape.coords(id2);
+        self.unlink_indices(x1, y1, x2, y2)
+    }
+
+    fn has_links_id(&self, id: usize) -> bool {
+        self.links.has_links(self.shape.coords(id))
+    }
+
+    /// All cells linked from the cell, including portals.
+    fn linked_ids(&self, id: usize) -> Vec<usize> {
+        self.links.from(self.shape.coords(id)).iter().map(|&(x, y)| self.shape.id(x, y)).collect()
+    }
+}
+
+/// Grids are equal when they have the same size, wrap and links, whatever
+/// their openings, weights and cells carry.
+impl <T> PartialEq for Grid<T>
+    where T: Cell + Clone
+{
+    fn eq(&self, other: &Grid<T>) -> bool {
+        self.x() == other.x() && self.y() == other.y() &&
+            self.shape.wrap() == other.shape.wrap() && self.links == other.links
+    }
+}
+
+impl <T> Eq for Grid<T>
+    where T: Cell + Clone
+{
+}
+
+impl <T> Hash for Grid<T>
+    where T: Cell + Clone
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fingerprint().hash(state)
     }
 }
 
@@ -247,10 +746,100 @@ impl <T> Index<usize> for Grid<T>
 }
 
 impl <T> IndexMut<usize> for Grid<T>
-    where T: Cell + Clone + Copy
+    where T: Cell + Clone
 {
     fn index_mut<'a>(&'a mut self, index: usize) -> &'a mut Vec<T> {
         &mut self.cells[index]
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::cell::BaseCell;
+    use super::super::rectangle::{Direction, Wrap};
+    use super::super::super::error::MazeError;
+
+    #[test]
+    fn test_generators_carve_around_mask() {
+        for &algorithm in ["aldous-broder", "binary", "sidewinder", "wilson"].iter() {
+            let mut grid: Grid<BaseCell> = Grid::new(5, 4);
+            // A wall of masked cells splits the grid in two
+            for y in 0..4 {
+                grid.set_masked(2, y, true);
+            }
+
+            match algorithm {
+                "aldous-broder" => grid.generate_aldous_broder(),
+                "binary" => grid.generate_binary(),
+                "sidewinder" => grid.generate_sidewinder(),
+                _ => grid.generate_wilson()
+            }
+
+            for y in 0..4 {
+                assert!(!grid.links.has_links((2, y)), "{} linked a masked cell", algorithm);
+            }
+
+            let report = grid.validate();
+            assert_eq!(report.cycles, 0);
+            assert!(report.non_adjacent.is_empty());
+            if algorithm == "aldous-broder" || algorithm == "wilson" {
+                assert_eq!(report.components.len(), 2);
+            }
+        }
+
+        let mut grid: Grid<BaseCell> = Grid::new(3, 3);
+        grid.generate_aldous_broder();
+        grid.set_link_weight(1, 1, 1, 0, 2);
+        grid.set_masked(1, 1, true);
+        assert!(!grid.links.has_links((1, 1)));
+        assert!(grid.links.pairs().iter().all(|&(_, to)| to != (1, 1)));
+        assert!(grid.link_weights().is_empty());
+    }
+
+    #[test]
+    fn test_open_directions() {
+        let mut grid: Grid<BaseCell> = Grid::with_wrap(3, 3, Wrap::Cylinder);
+        grid.link_indices(1, 1, 1, 0);
+        grid.link_one_way_indices(1, 1, 2, 1);
+        grid.link_indices(0, 1, 2, 1);
+
+        assert_eq!(grid.open_directions((1, 1)), vec![Direction::North, Direction::East]);
+        assert_eq!(grid.open_directions((2, 1)), vec![Direction::East, Direction::West]);
+        assert_eq!(grid.neighbor((2, 1), Direction::East), Some((0, 1)));
+        assert!(!grid.is_open((1, 0), Direction::North));
+    }
+
+    #[test]
+    fn test_fallible_variants() {
+        assert_eq!(Grid::<BaseCell>::try_new(0, 5).err(), Some(MazeError::EmptyGrid(0, 5)));
+
+        let mut grid: Grid<BaseCell> = Grid::try_new(2, 2).unwrap();
+        assert_eq!(grid.try_link_indices(1, 1, 2, 1), Err(MazeError::OutOfBounds(2, 1)));
+        assert!(!grid.links.has_links((1, 1)));
+        assert!(grid.try_link_indices(1, 1, 1, 0).is_ok());
+        assert!(grid.cell(1, 2).is_err());
+        assert!(grid.try_to_png(10, 2, &[255, 255, 255], &[0, 0, 0], "/nonexistent/maze.png").is_err());
+        assert!(grid.try_to_png_weights(10, 2, &[255, 255, 255], &[0, 0, 0], "/nonexistent/maze.png").is_err());
+
+        assert_eq!(grid.try_set_weight(1, 1, 0), Err(MazeError::ZeroWeight));
+        assert_eq!(grid.try_set_weight(2, 1, 3), Err(MazeError::OutOfBounds(2, 1)));
+        assert!(grid.try_set_weight(1, 1, 3).is_ok());
+        assert_eq!(grid.weight(1, 1), 3);
+        assert_eq!(grid.try_set_link_weight(1, 1, 1, 0, 0), Err(MazeError::ZeroWeight));
+        assert!(grid.try_set_link_weight(1, 1, 1, 0, 4).is_ok());
+        assert_eq!(grid.link_weight(1, 0, 1, 1), Some(4));
+
+        let mut empty: Grid<BaseCell> = Grid::new(0, 3);
+        assert_eq!(empty.try_place_corners(), Err(MazeError::EmptyGrid(0, 3)));
+        assert!(grid.try_place_corners().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "OutOfBounds")]
+    fn test_set_weight_checks_bounds() {
+        let mut grid: Grid<BaseCell> = Grid::new(2, 2);
+        grid.set_weight(2, 0, 3);
+    }
+}
@@ -0,0 +1,138 @@
+use super::maze::Maze;
+use super::topology::Topology;
+use super::super::error::MazeError;
+use super::super::output;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexDirection {
+    North,
+    NorthEast,
+    SouthEast,
+    South,
+    SouthWest,
+    NorthWest
+}
+
+pub const HEX_DIRECTIONS: [HexDirection; 6] = [
+    HexDirection::North,
+    HexDirection::NorthEast,
+    HexDirection::SouthEast,
+    HexDirection::South,
+    HexDirection::SouthWest,
+    HexDirection::NorthWest
+];
+
+/// Flat-topped hexagons in columns, odd columns shifted half a cell down
+/// ("odd-q" offset coordinates).
+///
+/// Ids are assigned as on a `Rectangle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hex {
+    x: usize,
+    y: usize
+}
+
+impl Hex {
+    pub fn new(x: usize, y: usize) -> Hex {
+        Hex {
+            x: x,
+            y: y
+        }
+    }
+
+    pub fn coords(&self, id: usize) -> (usize, usize) {
+        (id % self.x, id / self.x)
+    }
+
+    pub fn id(&self, x: usize, y: usize) -> usize {
+        y * self.x + x
+    }
+
+    pub fn neighbor(&self, x: usize, y: usize, direction: HexDirection) -> Option<(usize, usize)> {
+        let (x, y) = (x as isize, y as isize);
+
+        // Rows of the diagonal neighbours depend on the column parity
+        let (north_diagonal, south_diagonal) = match x % 2 {
+            0 => (y - 1, y),
+            _ => (y, y + 1)
+        };
+
+        let (nx, ny) = match direction {
+            HexDirection::North => (x, y - 1),
+            HexDirection::NorthEast => (x + 1, north_diagonal),
+            HexDirection::SouthEast => (x + 1, south_diagonal),
+            HexDirection::South => (x, y + 1),
+            HexDirection::SouthWest => (x - 1, south_diagonal),
+            HexDirection::NorthWest => (x - 1, north_diagonal)
+        };
+
+        if nx < 0 || ny < 0 || nx >= self.x as isize || ny >= self.y as isize {
+            return None;
+        }
+
+        Some((nx as usize, ny as usize))
+    }
+
+    pub fn neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        HEX_DIRECTIONS.iter().filter_map(|&d| self.neighbor(x, y, d)).collect()
+    }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn y(&self) -> usize {
+        self.y
+    }
+}
+
+impl Topology for Hex {
+    fn size(&self) -> usize {
+        self.x * self.y
+    }
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        let (x, y) = self.coords(id);
+        self.neighbors(x, y).iter().map(|&(nx, ny)| self.id(nx, ny)).collect()
+    }
+
+    fn is_boundary_id(&self, id: usize) -> bool {
+        let (x, y) = self.coords(id);
+        self.neighbors(x, y).len() < HEX_DIRECTIONS.len()
+    }
+}
+
+impl Maze<Hex> {
+    pub fn to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+        self.try_to_png(cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+    }
+
+    /// Same as `to_png`, failing when the image can't be written.
+    pub fn try_to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+        output::png_hex::try_format(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::topology::Topology;
+
+    #[test]
+    fn test_neighbors_are_symmetric() {
+        let hex = Hex::new(5, 4);
+        for id in 0..hex.size() {
+            for n in hex.neighbor_ids(id) {
+                assert!(hex.neighbor_ids(n).contains(&id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_interior_cell_has_six_neighbors() {
+        let hex = Hex::new(5, 4);
+        assert_eq!(hex.neighbors(1, 1).len(), 6);
+        assert_eq!(hex.neighbors(2, 1).len(), 6);
+        assert!(!hex.is_boundary_id(hex.id(2, 1)));
+    }
+}
@@ -0,0 +1,146 @@
+use super::maze::Maze;
+use super::topology::{Linkable, Topology};
+use super::super::error::MazeError;
+use super::super::output;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelDirection {
+    North,
+    East,
+    South,
+    West,
+    Up,
+    Down
+}
+
+pub const LEVEL_DIRECTIONS: [LevelDirection; 6] = [
+    LevelDirection::North,
+    LevelDirection::East,
+    LevelDirection::South,
+    LevelDirection::West,
+    LevelDirection::Up,
+    LevelDirection::Down
+];
+
+/// Stack of `z` rectangular levels, where a cell is also adjacent to the
+/// cell at the same position on the level above and below it.
+///
+/// Ids are assigned level by level, then row by row,
+/// `id = (level * height + row) * width + column`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Levels {
+    x: usize,
+    y: usize,
+    z: usize
+}
+
+impl Levels {
+    pub fn new(x: usize, y: usize, z: usize) -> Levels {
+        Levels {
+            x: x,
+            y: y,
+            z: z
+        }
+    }
+
+    pub fn coords(&self, id: usize) -> (usize, usize, usize) {
+        let level = self.x * self.y;
+        (id % self.x, (id % level) / self.x, id / level)
+    }
+
+    pub fn id(&self, x: usize, y: usize, z: usize) -> usize {
+        (z * self.y + y) * self.x + x
+    }
+
+    pub fn neighbor(&self, x: usize, y: usize, z: usize, direction: LevelDirection) -> Option<(usize, usize, usize)> {
+        match direction {
+            LevelDirection::North if y > 0 => Some((x, y - 1, z)),
+            LevelDirection::East if x < self.x - 1 => Some((x + 1, y, z)),
+            LevelDirection::South if y < self.y - 1 => Some((x, y + 1, z)),
+            LevelDirection::West if x > 0 => Some((x - 1, y, z)),
+            LevelDirection::Up if z < self.z - 1 => Some((x, y, z + 1)),
+            LevelDirection::Down if z > 0 => Some((x, y, z - 1)),
+            _ => None
+        }
+    }
+
+    pub fn neighbors(&self, x: usize, y: usize, z: usize) -> Vec<(usize, usize, usize)> {
+        LEVEL_DIRECTIONS.iter().filter_map(|&d| self.neighbor(x, y, z, d)).collect()
+    }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn y(&self) -> usize {
+        self.y
+    }
+
+    pub fn z(&self) -> usize {
+        self.z
+    }
+}
+
+impl Topology for Levels {
+    fn size(&self) -> usize {
+        self.x * self.y * self.z
+    }
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        let (x, y, z) = self.coords(id);
+        self.neighbors(x, y, z).iter().map(|&(nx, ny, nz)| self.id(nx, ny, nz)).collect()
+    }
+
+    /// Cells on the outer walls of their level.
+    fn is_boundary_id(&self, id: usize) -> bool {
+        let (x, y, _) = self.coords(id);
+        x == 0 || y == 0 || x == self.x - 1 || y == self.y - 1
+    }
+}
+
+impl Maze<Levels> {
+    /// Whether the cell is linked to its neighbour in the given direction,
+    /// for `Up` and `Down` whether it has stairs.
+    pub fn is_open(&self, x: usize, y: usize, z: usize, direction: LevelDirection) -> bool {
+        let levels = self.shape();
+        match levels.neighbor(x, y, z, direction) {
+            Some((nx, ny, nz)) => self.is_linked_ids(levels.id(x, y, z), levels.id(nx, ny, nz)),
+            None => false
+        }
+    }
+
+    pub fn print_ascii(&self) {
+        print!("{}", self.to_string());
+    }
+
+    pub fn to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+        self.try_to_png(cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+    }
+
+    /// Same as `to_png`, failing when the image can't be written.
+    pub fn try_to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+        output::png_levels::try_format(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+
+    pub fn to_string(&self) -> String {
+        output::ascii_levels::format(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::topology::Topology;
+
+    #[test]
+    fn test_neighbors_are_symmetric() {
+        let levels = Levels::new(4, 3, 3);
+        for id in 0..levels.size() {
+            let (x, y, z) = levels.coords(id);
+            assert_eq!(levels.id(x, y, z), id);
+            for n in levels.neighbor_ids(id) {
+                assert!(levels.neighbor_ids(n).contains(&id));
+            }
+        }
+    }
+}
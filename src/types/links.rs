@@ -0,0 +1,278 @@
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::mem;
+
+use super::rectangle::Rectangle;
+use super::topology::Topology;
+
+/// Bits of a cell, for its east and south neighbour.
+const BITS_PER_CELL: usize = 2;
+const EAST: usize = 0;
+const SOUTH: usize = 1;
+
+/// Links of a rectangular grid, packed into a bitset.
+///
+/// Every cell owns one bit for the two-way passage to its east and one for
+/// the one to its south neighbour (as returned by `Rectangle::east` and
+/// `Rectangle::south`, so wrapping edges are covered too). Passages to the
+/// north and west are the bits of the neighbour. One-way passages and
+/// links between cells which aren't adjacent, such as portals, are rare and
+/// kept aside in a map.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Links {
+    shape: Rectangle,
+    bits: Vec<u64>,
+    extra: HashMap<(usize, usize), BTreeSet<(usize, usize)>>
+}
+
+impl Links {
+    pub fn new(shape: Rectangle) -> Links {
+        let bits = shape.x() * shape.y() * BITS_PER_CELL;
+        Links {
+            shape: shape,
+            bits: vec![0; (bits + 63) / 64],
+            extra: HashMap::new()
+        }
+    }
+
+    /// Index of the bit of the passage between two cells, the same for
+    /// either order, `None` if the cells aren't adjacent.
+    fn bit(&self, a: (usize, usize), b: (usize, usize)) -> Option<usize> {
+        // Cells two columns apart on a wrapped grid are each other's east
+        // neighbour, the lower cell picks the bit
+        let (from, to) = match self.shape.id(a.0, a.1) <= self.shape.id(b.0, b.1) {
+            true => (a, b),
+            false => (b, a)
+        };
+
+        let (cell, bit) = if self.shape.east(from.0, from.1) == Some(to) {
+            (from, EAST)
+        } else if self.shape.east(to.0, to.1) == Some(from) {
+            (to, EAST)
+        } else if self.shape.south(from.0, from.1) == Some(to) {
+            (from, SOUTH)
+        } else if self.shape.south(to.0, to.1) == Some(from) {
+            (to, SOUTH)
+        } else {
+            return None;
+        };
+
+        Some(self.shape.id(cell.0, cell.1) * BITS_PER_CELL + bit)
+    }
+
+    /// Whether the two-way passage between adjacent cells is set.
+    fn both(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        match self.bit(a, b) {
+            Some(index) => self.get_bit(index),
+            None => false
+        }
+    }
+
+    fn get_bit(&self, index: usize) -> bool {
+        self.bits[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    fn set_bit(&mut self, index: usize, value: bool) {
+        match value {
+            true => self.bits[index / 64] |= 1 << (index % 64),
+            false => self.bits[index / 64] &= !(1 << (index % 64))
+        }
+    }
+
+    fn contains_extra(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        match self.extra.get(&from) {
+            Some(set) => set.contains(&to),
+            None => false
+        }
+    }
+
+    fn insert_extra(&mut self, from: (usize, usize), to: (usize, usize)) {
+        self.extra.entry(from).or_insert_with(BTreeSet::new).insert(to);
+    }
+
+    fn remove_extra(&mut self, from: (usize, usize), to: (usize, usize)) {
+        let mut empty = false;
+        if let Some(set) = self.extra.get_mut(&from) {
+            set.remove(&to);
+            empty = set.is_empty();
+        }
+
+        if empty {
+            self.extra.remove(&from);
+        }
+    }
+
+    pub fn contains(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        self.both(from, to) || self.contains_extra(from, to)
+    }
+
+    /// Cells linked from the cell, in order.
+    pub fn from(&self, cell: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut res: Vec<(usize, usize)> = self.shape.neighbors(cell.0, cell.1).into_iter()
+            .filter(|&neighbor| self.both(cell, neighbor))
+            .collect();
+
+        if let Some(set) = self.extra.get(&cell) {
+            res.extend(set.iter().cloned());
+        }
+
+        res.sort();
+        res.dedup();
+        res
+    }
+
+    /// Whether any link leads from the cell.
+    pub fn has_links(&self, cell: (usize, usize)) -> bool {
+        self.extra.contains_key(&cell) ||
+            self.shape.neighbors(cell.0, cell.1).into_iter().any(|neighbor| self.both(cell, neighbor))
+    }
+
+    /// Bytes allocated for the links: the bitset, and the entries of the map
+    /// of one-way passages and portals. The bookkeeping of the map itself
+    /// isn't counted.
+    pub fn heap_size(&self) -> usize {
+        let entry = mem::size_of::<((usize, usize), BTreeSet<(usize, usize)>)>();
+        let extra: usize = self.extra.values().map(|set| set.len() * mem::size_of::<(usize, usize)>()).sum();
+        self.bits.capacity() * mem::size_of::<u64>() + self.extra.capacity() * entry + extra
+    }
+
+    /// Adds the direction from `from` to `to`. A one-way passage between
+    /// adjacent cells becomes a two-way one once the way back is added.
+    pub fn insert(&mut self, from: (usize, usize), to: (usize, usize)) {
+        match self.bit(from, to) {
+            Some(index) if self.contains_extra(to, from) => {
+                self.remove_extra(to, from);
+                self.set_bit(index, true);
+            },
+            Some(index) if self.get_bit(index) => {},
+            _ => self.insert_extra(from, to)
+        }
+    }
+
+    /// Adds both directions between the cells, a two-way passage.
+    pub fn insert_both(&mut self, a: (usize, usize), b: (usize, usize)) {
+        match self.bit(a, b) {
+            Some(index) => {
+                self.remove_extra(a, b);
+                self.remove_extra(b, a);
+                self.set_bit(index, true);
+            },
+            None => {
+                self.insert_extra(a, b);
+                self.insert_extra(b, a);
+            }
+        }
+    }
+
+    /// Every link as a pair of cells, in order.
+    pub fn pairs(&self) -> Vec<((usize, usize), (usize, usize))> {
+        let mut res = Vec::new();
+        for id in 0..self.shape.size() {
+            let cell = self.shape.coords(id);
+            for to in self.from(cell) {
+                res.push((cell, to));
+            }
+        }
+
+        res
+    }
+
+    /// Removes the direction from `from` to `to`, a two-way passage between
+    /// adjacent cells becoming a one-way passage back.
+    pub fn remove(&mut self, from: (usize, usize), to: (usize, usize)) {
+        match self.bit(from, to) {
+            Some(index) if self.get_bit(index) => {
+                self.set_bit(index, false);
+                self.insert_extra(to, from);
+            },
+            _ => self.remove_extra(from, to)
+        }
+    }
+
+    /// Removes every link from and to the cell.
+    pub fn remove_cell(&mut self, cell: (usize, usize)) {
+        for neighbor in self.shape.neighbors(cell.0, cell.1) {
+            if let Some(index) = self.bit(cell, neighbor) {
+                self.set_bit(index, false);
+            }
+        }
+
+        self.extra.remove(&cell);
+        for set in self.extra.values_mut() {
+            set.remove(&cell);
+        }
+        self.extra.retain(|_, set| !set.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::rectangle::{Rectangle, Wrap};
+
+    #[test]
+    fn test_directions() {
+        let mut links = Links::new(Rectangle::new(3, 3));
+        links.insert((1, 1), (2, 1));
+        assert!(links.contains((1, 1), (2, 1)));
+        assert!(!links.contains((2, 1), (1, 1)));
+        assert!(links.has_links((1, 1)));
+        assert!(!links.has_links((2, 1)));
+
+        links.insert((1, 1), (1, 0));
+        assert_eq!(links.from((1, 1)), vec![(1, 0), (2, 1)]);
+
+        links.remove((1, 1), (2, 1));
+        assert_eq!(links.pairs(), vec![((1, 1), (1, 0))]);
+    }
+
+    #[test]
+    fn test_two_way_passages_take_one_bit() {
+        let mut links = Links::new(Rectangle::new(3, 3));
+        links.insert((1, 1), (2, 1));
+        links.insert((2, 1), (1, 1));
+        links.insert_both((1, 1), (1, 0));
+        assert!(links.extra.is_empty());
+        assert_eq!(links.bits, vec![(1 << (4 * BITS_PER_CELL + EAST)) | (1 << (BITS_PER_CELL + SOUTH))]);
+        assert_eq!(links.from((1, 1)), vec![(1, 0), (2, 1)]);
+
+        // Removing one direction leaves the way back
+        links.remove((1, 1), (2, 1));
+        assert!(!links.contains((1, 1), (2, 1)));
+        assert!(links.contains((2, 1), (1, 1)));
+        assert_eq!(links.from((2, 1)), vec![(1, 1)]);
+
+        // Cells two columns apart on a cylinder share one bit either way
+        let mut links = Links::new(Rectangle::with_wrap(2, 1, Wrap::Cylinder));
+        links.insert_both((1, 0), (0, 0));
+        assert!(links.contains((0, 0), (1, 0)));
+        assert_eq!(links.pairs(), vec![((0, 0), (1, 0)), ((1, 0), (0, 0))]);
+    }
+
+    #[test]
+    fn test_wrapping_and_portals() {
+        let mut links = Links::new(Rectangle::with_wrap(4, 3, Wrap::Mobius));
+        links.insert((0, 0), (3, 2));
+        assert!(links.contains((0, 0), (3, 2)));
+        assert!(!links.contains((0, 0), (3, 0)));
+
+        links.insert((0, 1), (2, 2));
+        assert!(links.contains((0, 1), (2, 2)));
+        assert_eq!(links.from((0, 1)), vec![(2, 2)]);
+        links.remove((0, 1), (2, 2));
+        assert!(!links.has_links((0, 1)));
+    }
+
+    #[test]
+    fn test_remove_cell() {
+        let mut links = Links::new(Rectangle::new(3, 3));
+        links.insert((1, 1), (2, 1));
+        links.insert((1, 0), (1, 1));
+        links.insert((0, 0), (1, 1));
+        links.insert((1, 1), (2, 2));
+        links.insert((0, 0), (2, 2));
+
+        links.remove_cell((1, 1));
+        assert_eq!(links.pairs(), vec![((0, 0), (2, 2))]);
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+use super::topology::{Linkable, Topology};
+use super::super::error::MazeError;
+use super::super::generator;
+
+/// Maze over an arbitrary topology, with links keyed by cell id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Maze<S>
+    where S: Topology
+{
+    shape: S,
+    pub links: HashMap<usize, BTreeSet<usize>>
+}
+
+impl <S> Maze<S>
+    where S: Topology
+{
+    pub fn new(shape: S) -> Maze<S> {
+        Maze {
+            shape: shape,
+            links: HashMap::new()
+        }
+    }
+
+    /// Same as `new`, failing on shapes without any cells, which generators
+    /// can't carve.
+    pub fn try_new(shape: S) -> Result<Maze<S>, MazeError> {
+        match shape.size() {
+            0 => Err(MazeError::EmptyMaze),
+            _ => Ok(Maze::new(shape))
+        }
+    }
+
+    pub fn generate_aldous_broder(&mut self) {
+        generator::aldous_broder::generate(self)
+    }
+
+    pub fn generate_wilson(&mut self) {
+        generator::wilson::generate(self)
+    }
+
+    pub fn link_pair(&mut self, id1: usize, id2: usize) {
+        self.links.entry(id1).or_insert_with(BTreeSet::new).insert(id2);
+    }
+
+    pub fn shape(&self) -> &S {
+        &self.shape
+    }
+
+    pub fn unlink_pair(&mut self, id1: usize, id2: usize) {
+        let mut remove = false;
+        if let Some(set) = self.links.get_mut(&id1) {
+            set.remove(&id2);
+            remove = set.is_empty();
+        }
+
+        if remove {
+            self.links.remove(&id1);
+        }
+    }
+}
+
+impl <S> Topology for Maze<S>
+    where S: Topology
+{
+    fn size(&self) -> usize {
+        self.shape.size()
+    }
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        self.shape.neighbor_ids(id)
+    }
+
+    fn is_boundary_id(&self, id: usize) -> bool {
+        self.shape.is_boundary_id(id)
+    }
+}
+
+impl <S> Linkable for Maze<S>
+    where S: Topology
+{
+    fn is_linked_ids(&self, id1: usize, id2: usize) -> bool {
+        match self.links.get(&id1) {
+            Some(set) => set.contains(&id2),
+            None => false
+        }
+    }
+
+    fn link_ids(&mut self, id1: usize, id2: usize) {
+        self.link_pair(id1, id2);
+        self.link_pair(id2, id1);
+    }
+
+    fn unlink_ids(&mut self, id1: usize, id2: usize) {
+        self.unlink_pair(id1, id2);
+        self.unlink_pair(id2, id1);
+    }
+
+    fn has_links_id(&self, id: usize) -> bool {
+        self.links.contains_key(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Maze;
+    use super::super::cube::Cube;
+    use super::super::delta::Delta;
+    use super::super::hex::Hex;
+    use super::super::levels::Levels;
+    use super::super::polar::Polar;
+    use super::super::rectangle::Rectangle;
+    use super::super::upsilon::Upsilon;
+    use super::super::voronoi::Voronoi;
+    use super::super::topology::Topology;
+    use super::super::super::distance;
+    use super::super::super::error::MazeError;
+
+    #[test]
+    fn test_try_new_rejects_empty_shapes() {
+        assert_eq!(Maze::try_new(Hex::new(0, 3)).err(), Some(MazeError::EmptyMaze));
+        assert!(Maze::try_new(Hex::new(1, 3)).is_ok());
+    }
+
+    #[test]
+    fn test_try_to_png_reports_unwritable_files() {
+        let (white, black, filename) = (&[255, 255, 255], &[0, 0, 0], "/nonexistent/maze.png");
+        assert!(Maze::new(Cube::new(2)).try_to_png(10, 2, white, black, filename).is_err());
+        assert!(Maze::new(Delta::new(3, 2)).try_to_png(10, 2, white, black, filename).is_err());
+        assert!(Maze::new(Hex::new(3, 2)).try_to_png(10, 2, white, black, filename).is_err());
+        assert!(Maze::new(Levels::new(3, 2, 2)).try_to_png(10, 2, white, black, filename).is_err());
+        assert!(Maze::new(Polar::new(2)).try_to_png(10, 2, white, black, filename).is_err());
+        assert!(Maze::new(Upsilon::new(3, 2)).try_to_png(10, 2, white, black, filename).is_err());
+        assert!(Maze::new(Voronoi::new(3, 2, 1)).try_to_png(10, 2, white, black, filename).is_err());
+    }
+
+    #[test]
+    fn test_aldous_broder_connects_every_cell() {
+        let mut maze = Maze::new(Rectangle::new(7, 5));
+        maze.generate_aldous_broder();
+
+        let distances = distance::dijkstra::distances(&maze, 0);
+        for id in 0..maze.size() {
+            assert!(distances.get(id).is_some());
+        }
+    }
+}
@@ -1,2 +1,17 @@
 pub mod cell;
-pub mod grid;
\ No newline at end of file
+pub mod cube;
+pub mod delta;
+pub mod fingerprint;
+pub mod graph;
+pub mod grid;
+pub mod hex;
+pub mod levels;
+pub mod links;
+pub mod maze;
+pub mod opening;
+pub mod polar;
+pub mod rectangle;
+pub mod topology;
+pub mod upsilon;
+pub mod voronoi;
+pub mod world;
@@ -0,0 +1,23 @@
+use super::rectangle::Direction;
+
+/// Gap in the outer wall of a boundary cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Opening {
+    pub x: usize,
+    pub y: usize,
+    pub side: Direction
+}
+
+impl Opening {
+    pub fn new(x: usize, y: usize, side: Direction) -> Opening {
+        Opening {
+            x: x,
+            y: y,
+            side: side
+        }
+    }
+
+    pub fn cell(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+}
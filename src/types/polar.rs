@@ -0,0 +1,184 @@
+use std::f32::consts::PI;
+
+use super::maze::Maze;
+use super::topology::Topology;
+use super::super::error::MazeError;
+use super::super::output;
+
+/// Concentric rings around a single center cell.
+///
+/// Each ring is split into as many cells as keeps them roughly square, so
+/// a ring has either the same number of cells as the one inside it or an
+/// integer multiple of it. Cells are addressed by `(ring, index)`, where the
+/// index grows clockwise. Ids are assigned ring by ring from the center.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Polar {
+    rings: Vec<usize>,
+    offsets: Vec<usize>
+}
+
+impl Polar {
+    pub fn new(rings: usize) -> Polar {
+        let mut counts: Vec<usize> = Vec::with_capacity(rings);
+        let mut offsets: Vec<usize> = Vec::with_capacity(rings);
+        let row_height = 1.0 / rings as f32;
+
+        for ring in 0..rings {
+            let count = match ring {
+                0 => 1,
+                _ => {
+                    let previous = counts[ring - 1];
+                    let circumference = 2.0 * PI * ring as f32 * row_height;
+                    let estimated_width = circumference / previous as f32;
+                    let ratio = (estimated_width / row_height).round().max(1.0) as usize;
+                    previous * ratio
+                }
+            };
+
+            offsets.push(match ring {
+                0 => 0,
+                _ => offsets[ring - 1] + counts[ring - 1]
+            });
+            counts.push(count);
+        }
+
+        Polar {
+            rings: counts,
+            offsets: offsets
+        }
+    }
+
+    pub fn coords(&self, id: usize) -> (usize, usize) {
+        let mut ring = self.rings.len() - 1;
+        while self.offsets[ring] > id {
+            ring -= 1;
+        }
+
+        (ring, id - self.offsets[ring])
+    }
+
+    /// Number of cells in the ring.
+    pub fn count(&self, ring: usize) -> usize {
+        self.rings[ring]
+    }
+
+    pub fn id(&self, ring: usize, index: usize) -> usize {
+        self.offsets[ring] + index
+    }
+
+    pub fn clockwise(&self, ring: usize, index: usize) -> Option<(usize, usize)> {
+        match self.rings[ring] {
+            1 => None,
+            count => Some((ring, (index + 1) % count))
+        }
+    }
+
+    pub fn counter_clockwise(&self, ring: usize, index: usize) -> Option<(usize, usize)> {
+        match self.rings[ring] {
+            1 => None,
+            count => Some((ring, (index + count - 1) % count))
+        }
+    }
+
+    pub fn inward(&self, ring: usize, index: usize) -> Option<(usize, usize)> {
+        match ring {
+            0 => None,
+            _ => {
+                let ratio = self.rings[ring] / self.rings[ring - 1];
+                Some((ring - 1, index / ratio))
+            }
+        }
+    }
+
+    pub fn outward(&self, ring: usize, index: usize) -> Vec<(usize, usize)> {
+        if ring + 1 >= self.rings.len() {
+            return Vec::new();
+        }
+
+        let ratio = self.rings[ring + 1] / self.rings[ring];
+        (index * ratio..(index + 1) * ratio).map(|i| (ring + 1, i)).collect()
+    }
+
+    pub fn neighbors(&self, ring: usize, index: usize) -> Vec<(usize, usize)> {
+        let mut res = Vec::new();
+
+        if let Some(cell) = self.inward(ring, index) {
+            res.push(cell);
+        }
+
+        if let Some(cell) = self.clockwise(ring, index) {
+            res.push(cell);
+        }
+
+        if let Some(cell) = self.counter_clockwise(ring, index) {
+            if !res.contains(&cell) {
+                res.push(cell);
+            }
+        }
+
+        res.extend(self.outward(ring, index));
+
+        return res;
+    }
+
+    pub fn rings(&self) -> usize {
+        self.rings.len()
+    }
+}
+
+impl Topology for Polar {
+    fn size(&self) -> usize {
+        match self.rings.len() {
+            0 => 0,
+            len => self.offsets[len - 1] + self.rings[len - 1]
+        }
+    }
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        let (ring, index) = self.coords(id);
+        self.neighbors(ring, index).iter().map(|&(r, i)| self.id(r, i)).collect()
+    }
+
+    fn is_boundary_id(&self, id: usize) -> bool {
+        self.coords(id).0 == self.rings.len() - 1
+    }
+}
+
+impl Maze<Polar> {
+    pub fn to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+        self.try_to_png(cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+    }
+
+    /// Same as `to_png`, failing when the image can't be written.
+    pub fn try_to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+        output::png_polar::try_format(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::topology::Topology;
+
+    #[test]
+    fn test_rings_are_subdivided() {
+        let polar = Polar::new(8);
+        assert_eq!(polar.count(0), 1);
+        assert_eq!(polar.count(1), 6);
+        for ring in 1..polar.rings() {
+            assert_eq!(polar.count(ring) % polar.count(ring - 1), 0);
+        }
+        assert!(polar.count(7) > polar.count(1));
+    }
+
+    #[test]
+    fn test_neighbors_are_symmetric() {
+        let polar = Polar::new(6);
+        for id in 0..polar.size() {
+            assert_eq!(polar.id(polar.coords(id).0, polar.coords(id).1), id);
+            for n in polar.neighbor_ids(id) {
+                assert!(polar.neighbor_ids(n).contains(&id));
+            }
+        }
+    }
+}
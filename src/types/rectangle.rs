@@ -0,0 +1,298 @@
+use super::topology::Topology;
+
+/// Which opposite edges of a rectangle are joined together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Wrap {
+    None,
+    /// West and east edges are joined.
+    Cylinder,
+    /// West and east, and north and south edges are joined.
+    Torus,
+    /// West and east edges are joined upside down.
+    Mobius,
+    /// West and east edges are joined upside down, north and south edges
+    /// are joined.
+    Klein
+}
+
+impl Wrap {
+    /// Wrap with the given name, as returned by `name`.
+    pub fn from_name(name: &str) -> Option<Wrap> {
+        match name {
+            "none" => Some(Wrap::None),
+            "cylinder" => Some(Wrap::Cylinder),
+            "torus" => Some(Wrap::Torus),
+            "mobius" => Some(Wrap::Mobius),
+            "klein" => Some(Wrap::Klein),
+            _ => None
+        }
+    }
+
+    /// Whether the west and east edges are joined upside down.
+    pub fn flips_x(&self) -> bool {
+        *self == Wrap::Mobius || *self == Wrap::Klein
+    }
+
+    /// Name of the wrap, as accepted by `--wrap`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Wrap::None => "none",
+            Wrap::Cylinder => "cylinder",
+            Wrap::Torus => "torus",
+            Wrap::Mobius => "mobius",
+            Wrap::Klein => "klein"
+        }
+    }
+
+    pub fn wraps_x(&self) -> bool {
+        *self != Wrap::None
+    }
+
+    pub fn wraps_y(&self) -> bool {
+        *self == Wrap::Torus || *self == Wrap::Klein
+    }
+}
+
+/// Side of a cell of a rectangle, towards one of its neighbours or through
+/// the outer wall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West
+}
+
+pub const DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West
+];
+
+impl Direction {
+    /// Direction with the given name, as returned by `name`.
+    pub fn from_name(name: &str) -> Option<Direction> {
+        match name {
+            "north" => Some(Direction::North),
+            "east" => Some(Direction::East),
+            "south" => Some(Direction::South),
+            "west" => Some(Direction::West),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Direction::North => "north",
+            Direction::East => "east",
+            Direction::South => "south",
+            Direction::West => "west"
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match *self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East
+        }
+    }
+}
+
+/// Rectangular topology with 4-neighbour adjacency, optionally wrapping
+/// around its edges.
+///
+/// Ids are assigned row by row, cell (x, y) getting `id = y * width + x`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rectangle {
+    x: usize,
+    y: usize,
+    wrap: Wrap
+}
+
+impl Rectangle {
+    pub fn new(x: usize, y: usize) -> Rectangle {
+        Rectangle::with_wrap(x, y, Wrap::None)
+    }
+
+    pub fn with_wrap(x: usize, y: usize, wrap: Wrap) -> Rectangle {
+        Rectangle {
+            x: x,
+            y: y,
+            wrap: wrap
+        }
+    }
+
+    pub fn coords(&self, id: usize) -> (usize, usize) {
+        (id % self.x, id / self.x)
+    }
+
+    pub fn east(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if x < self.x - 1 {
+            Some((x + 1, y))
+        } else if self.wrap.wraps_x() && self.x > 1 {
+            Some((0, self.seam_y(y)))
+        } else {
+            None
+        }
+    }
+
+    pub fn id(&self, x: usize, y: usize) -> usize {
+        y * self.x + x
+    }
+
+    /// Whether the cell lies on an edge which is not joined to another one.
+    pub fn is_boundary(&self, x: usize, y: usize) -> bool {
+        (!self.wrap.wraps_x() && (x == 0 || x == self.x - 1)) ||
+            (!self.wrap.wraps_y() && (y == 0 || y == self.y - 1))
+    }
+
+    /// Neighbour in the given direction, across a joined edge if need be.
+    pub fn neighbor(&self, x: usize, y: usize, direction: Direction) -> Option<(usize, usize)> {
+        match direction {
+            Direction::North => self.north(x, y),
+            Direction::East => self.east(x, y),
+            Direction::South => self.south(x, y),
+            Direction::West => self.west(x, y)
+        }
+    }
+
+    pub fn neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut res = Vec::with_capacity(4);
+
+        for &direction in [Direction::West, Direction::East, Direction::North, Direction::South].iter() {
+            match self.neighbor(x, y, direction) {
+                // Narrow wrapped rectangles reach the same cell both ways
+                Some(cell) if !res.contains(&cell) => res.push(cell),
+                _ => {}
+            }
+        }
+
+        return res;
+    }
+
+    pub fn north(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if y > 0 {
+            Some((x, y - 1))
+        } else if self.wrap.wraps_y() && self.y > 1 {
+            Some((x, self.y - 1))
+        } else {
+            None
+        }
+    }
+
+    /// Row reached by crossing the west-east seam from row `y`.
+    fn seam_y(&self, y: usize) -> usize {
+        match self.wrap.flips_x() {
+            true => self.y - 1 - y,
+            false => y
+        }
+    }
+
+    pub fn south(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if y < self.y - 1 {
+            Some((x, y + 1))
+        } else if self.wrap.wraps_y() && self.y > 1 {
+            Some((x, 0))
+        } else {
+            None
+        }
+    }
+
+    pub fn west(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if x > 0 {
+            Some((x - 1, y))
+        } else if self.wrap.wraps_x() && self.x > 1 {
+            Some((self.x - 1, self.seam_y(y)))
+        } else {
+            None
+        }
+    }
+
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn y(&self) -> usize {
+        self.y
+    }
+}
+
+impl Topology for Rectangle {
+    fn size(&self) -> usize {
+        self.x * self.y
+    }
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        let (x, y) = self.coords(id);
+        self.neighbors(x, y).iter().map(|&(nx, ny)| self.id(nx, ny)).collect()
+    }
+
+    fn is_boundary_id(&self, id: usize) -> bool {
+        let (x, y) = self.coords(id);
+        self.is_boundary(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::topology::Topology;
+
+    #[test]
+    fn test_torus_has_no_boundary() {
+        let rectangle = Rectangle::with_wrap(4, 3, Wrap::Torus);
+        assert!(rectangle.boundary_ids().is_empty());
+        for id in 0..rectangle.size() {
+            assert_eq!(rectangle.neighbor_ids(id).len(), 4);
+        }
+    }
+
+    #[test]
+    fn test_cylinder_wraps_west_and_east() {
+        let rectangle = Rectangle::with_wrap(4, 3, Wrap::Cylinder);
+        assert_eq!(rectangle.west(0, 1), Some((3, 1)));
+        assert_eq!(rectangle.east(3, 1), Some((0, 1)));
+        assert_eq!(rectangle.north(2, 0), None);
+        assert!(!rectangle.is_boundary(0, 1));
+        assert!(rectangle.is_boundary(0, 0));
+    }
+
+    #[test]
+    fn test_neighbor_in_direction() {
+        let rectangle = Rectangle::new(4, 3);
+        assert_eq!(rectangle.neighbor(1, 1, Direction::North), Some((1, 0)));
+        assert_eq!(rectangle.neighbor(1, 1, Direction::East), Some((2, 1)));
+        assert_eq!(rectangle.neighbor(1, 1, Direction::South), Some((1, 2)));
+        assert_eq!(rectangle.neighbor(1, 1, Direction::West), Some((0, 1)));
+        assert_eq!(rectangle.neighbor(0, 0, Direction::West), None);
+        for &direction in DIRECTIONS.iter() {
+            assert_eq!(direction.opposite().opposite(), direction);
+        }
+    }
+
+    #[test]
+    fn test_mobius_flips_west_and_east() {
+        let rectangle = Rectangle::with_wrap(4, 3, Wrap::Mobius);
+        assert_eq!(rectangle.east(3, 0), Some((0, 2)));
+        assert_eq!(rectangle.west(0, 2), Some((3, 0)));
+        assert_eq!(rectangle.east(3, 1), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_klein_neighbors_are_symmetric() {
+        let rectangle = Rectangle::with_wrap(5, 4, Wrap::Klein);
+        assert!(rectangle.boundary_ids().is_empty());
+        for id in 0..rectangle.size() {
+            for n in rectangle.neighbor_ids(id) {
+                assert!(rectangle.neighbor_ids(n).contains(&id));
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+extern crate rand;
+
+use rand::distributions::{IndependentSample, Range};
+
+/// Shape of a maze - which cells exist and which of them are adjacent.
+///
+/// Cells are identified by ids in `0..size()`.
+pub trait Topology {
+    fn size(&self) -> usize;
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize>;
+
+    /// Whether the cell touches the outer edge of the maze.
+    fn is_boundary_id(&self, id: usize) -> bool;
+
+    /// Groups of cells reachable from each other through neighbours, each
+    /// in order, by their first cell. Shapes with holes, such as masked
+    /// grids, may have more than one.
+    fn regions(&self) -> Vec<Vec<usize>> {
+        let mut res = Vec::new();
+        let mut seen = vec![false; self.size()];
+        for id in 0..self.size() {
+            if seen[id] {
+                continue;
+            }
+
+            seen[id] = true;
+            let mut region = Vec::new();
+            let mut stack = vec![id];
+            while let Some(current) = stack.pop() {
+                region.push(current);
+                for neighbor in self.neighbor_ids(current) {
+                    if !seen[neighbor] {
+                        seen[neighbor] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+
+            region.sort();
+            res.push(region);
+        }
+
+        res
+    }
+
+    fn boundary_ids(&self) -> Vec<usize> {
+        (0..self.size()).filter(|&id| self.is_boundary_id(id)).collect()
+    }
+
+    fn random_id(&self) -> usize {
+        let between = Range::new(0, self.size());
+        between.ind_sample(&mut rand::thread_rng())
+    }
+}
+
+/// Topology whose adjacent cells can be linked into passages.
+pub trait Linkable: Topology {
+    /// Cost of moving from a cell to its linked neighbour, at least 1.
+    fn cost_ids(&self, _from: usize, _to: usize) -> usize {
+        1
+    }
+
+    fn is_linked_ids(&self, id1: usize, id2: usize) -> bool;
+
+    fn link_ids(&mut self, id1: usize, id2: usize);
+
+    fn unlink_ids(&mut self, id1: usize, id2: usize);
+
+    fn linked_ids(&self, id: usize) -> Vec<usize> {
+        self.neighbor_ids(id).into_iter().filter(|&n| self.is_linked_ids(id, n)).collect()
+    }
+
+    fn has_links_id(&self, id: usize) -> bool {
+        !self.linked_ids(id).is_empty()
+    }
+}
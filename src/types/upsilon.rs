@@ -0,0 +1,161 @@
+use super::maze::Maze;
+use super::topology::Topology;
+use super::super::error::MazeError;
+use super::super::output;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsilonDirection {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest
+}
+
+const OCTAGON_DIRECTIONS: [UpsilonDirection; 8] = [
+    UpsilonDirection::North,
+    UpsilonDirection::NorthEast,
+    UpsilonDirection::East,
+    UpsilonDirection::SouthEast,
+    UpsilonDirection::South,
+    UpsilonDirection::SouthWest,
+    UpsilonDirection::West,
+    UpsilonDirection::NorthWest
+];
+
+const SQUARE_DIRECTIONS: [UpsilonDirection; 4] = [
+    UpsilonDirection::North,
+    UpsilonDirection::East,
+    UpsilonDirection::South,
+    UpsilonDirection::West
+];
+
+/// Octagons and squares in a checkerboard (truncated square tiling).
+///
+/// Cell (x, y) is an octagon when `x + y` is even and a square otherwise.
+/// Octagons border the octagons on their diagonals and the squares on their
+/// sides, squares only border octagons. Ids are assigned as on a
+/// `Rectangle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Upsilon {
+    x: usize,
+    y: usize
+}
+
+impl Upsilon {
+    pub fn new(x: usize, y: usize) -> Upsilon {
+        Upsilon {
+            x: x,
+            y: y
+        }
+    }
+
+    pub fn coords(&self, id: usize) -> (usize, usize) {
+        (id % self.x, id / self.x)
+    }
+
+    pub fn directions(&self, x: usize, y: usize) -> &'static [UpsilonDirection] {
+        match self.is_octagon(x, y) {
+            true => &OCTAGON_DIRECTIONS,
+            false => &SQUARE_DIRECTIONS
+        }
+    }
+
+    pub fn id(&self, x: usize, y: usize) -> usize {
+        y * self.x + x
+    }
+
+    pub fn is_octagon(&self, x: usize, y: usize) -> bool {
+        (x + y) % 2 == 0
+    }
+
+    pub fn neighbor(&self, x: usize, y: usize, direction: UpsilonDirection) -> Option<(usize, usize)> {
+        if !self.directions(x, y).contains(&direction) {
+            return None;
+        }
+
+        let (dx, dy) = match direction {
+            UpsilonDirection::North => (0, -1),
+            UpsilonDirection::NorthEast => (1, -1),
+            UpsilonDirection::East => (1, 0),
+            UpsilonDirection::SouthEast => (1, 1),
+            UpsilonDirection::South => (0, 1),
+            UpsilonDirection::SouthWest => (-1, 1),
+            UpsilonDirection::West => (-1, 0),
+            UpsilonDirection::NorthWest => (-1, -1)
+        };
+
+        let nx = x as isize + dx;
+        let ny = y as isize + dy;
+        if nx < 0 || ny < 0 || nx >= self.x as isize || ny >= self.y as isize {
+            return None;
+        }
+
+        Some((nx as usize, ny as usize))
+    }
+
+    pub fn neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        self.directions(x, y).iter().filter_map(|&d| self.neighbor(x, y, d)).collect()
+    }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn y(&self) -> usize {
+        self.y
+    }
+}
+
+impl Topology for Upsilon {
+    fn size(&self) -> usize {
+        self.x * self.y
+    }
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        let (x, y) = self.coords(id);
+        self.neighbors(x, y).iter().map(|&(nx, ny)| self.id(nx, ny)).collect()
+    }
+
+    fn is_boundary_id(&self, id: usize) -> bool {
+        let (x, y) = self.coords(id);
+        self.neighbors(x, y).len() < self.directions(x, y).len()
+    }
+}
+
+impl Maze<Upsilon> {
+    pub fn to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+        self.try_to_png(cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+    }
+
+    /// Same as `to_png`, failing when the image can't be written.
+    pub fn try_to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+        output::png_upsilon::try_format(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::topology::Topology;
+
+    #[test]
+    fn test_neighbors_are_symmetric() {
+        let upsilon = Upsilon::new(5, 6);
+        for id in 0..upsilon.size() {
+            for n in upsilon.neighbor_ids(id) {
+                assert!(upsilon.neighbor_ids(n).contains(&id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_octagons_and_squares() {
+        let upsilon = Upsilon::new(5, 5);
+        assert_eq!(upsilon.neighbors(2, 2).len(), 8);
+        assert_eq!(upsilon.neighbors(2, 1).len(), 4);
+    }
+}
@@ -0,0 +1,293 @@
+extern crate rand;
+
+use rand::{Rng, SeedableRng, StdRng};
+
+use super::maze::Maze;
+use super::topology::Topology;
+use super::super::error::MazeError;
+use super::super::output;
+
+type Point = (f32, f32);
+
+/// Number of Lloyd relaxation steps, moving every point to the centroid of
+/// its cell, which evens out the cell sizes.
+const RELAXATION_STEPS: usize = 2;
+
+/// Edges shorter than this, in cells, don't make the cells adjacent.
+const MIN_EDGE: f32 = 0.05;
+
+fn distance(a: Point, b: Point) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// Clips the convex polygon to the half-plane of points closer to `site`
+/// than to `other`. Every vertex carries the label of the edge leaving it,
+/// edges along the cut get the label `cut`.
+fn clip(polygon: &[(Point, Option<usize>)], site: Point, other: Point, cut: Option<usize>) -> Vec<(Point, Option<usize>)> {
+    let middle = ((site.0 + other.0) / 2.0, (site.1 + other.1) / 2.0);
+    let normal = (other.0 - site.0, other.1 - site.1);
+    let side = |p: Point| (p.0 - middle.0) * normal.0 + (p.1 - middle.1) * normal.1;
+
+    let mut res = Vec::with_capacity(polygon.len() + 1);
+    for i in 0..polygon.len() {
+        let (a, label) = polygon[i];
+        let (b, _) = polygon[(i + 1) % polygon.len()];
+        let (side_a, side_b) = (side(a), side(b));
+
+        let crossing = || {
+            let t = side_a / (side_a - side_b);
+            (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+        };
+
+        match (side_a <= 0.0, side_b <= 0.0) {
+            (true, true) => res.push((a, label)),
+            (true, false) => {
+                res.push((a, label));
+                res.push((crossing(), cut));
+            },
+            (false, true) => res.push((crossing(), label)),
+            (false, false) => {}
+        }
+    }
+
+    return res;
+}
+
+fn centroid(polygon: &[Point]) -> Point {
+    let mut area = 0.0;
+    let mut x = 0.0;
+    let mut y = 0.0;
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        let cross = a.0 * b.1 - b.0 * a.1;
+        area += cross;
+        x += (a.0 + b.0) * cross;
+        y += (a.1 + b.1) * cross;
+    }
+
+    (x / (3.0 * area), y / (3.0 * area))
+}
+
+/// Voronoi cells of all points inside the `width x height` rectangle, as
+/// labelled polygons.
+fn tessellate(points: &[Point], width: f32, height: f32) -> Vec<Vec<(Point, Option<usize>)>> {
+    points.iter().enumerate().map(|(i, &site)| {
+        let mut polygon = vec![((0.0, 0.0), None), ((width, 0.0), None), ((width, height), None), ((0.0, height), None)];
+        for (j, &other) in points.iter().enumerate() {
+            if i != j && !polygon.is_empty() {
+                polygon = clip(&polygon, site, other, Some(j));
+            }
+        }
+        polygon
+    }).collect()
+}
+
+/// Irregular cells from the Voronoi tessellation of random points inside a
+/// rectangle, optionally limited to a mask.
+///
+/// Coordinates are in cells, so a `width x height` rectangle holds
+/// `width * height` cells of about the size of a grid cell. Ids are the
+/// indices of the cells, in no particular order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Voronoi {
+    width: f32,
+    height: f32,
+    sites: Vec<Point>,
+    polygons: Vec<Vec<Point>>,
+    across: Vec<Vec<Option<usize>>>,
+    neighbors: Vec<Vec<usize>>
+}
+
+impl Voronoi {
+    /// Tessellation of `width * height` points placed using the seed, so
+    /// the same seed always gives the same cells.
+    pub fn new(width: usize, height: usize, seed: usize) -> Voronoi {
+        Voronoi::with_mask(&vec![vec![true; height]; width], seed)
+    }
+
+    /// Tessellation of the squares of the mask (indexed `[x][y]`) which are
+    /// `true`. Points are placed over the whole rectangle and the cells
+    /// whose point falls outside the mask are dropped, so the outline
+    /// follows the mask along Voronoi edges. Only the largest connected
+    /// group of cells is kept.
+    pub fn with_mask(mask: &Vec<Vec<bool>>, seed: usize) -> Voronoi {
+        let width = mask.len();
+        let height = match mask.first() {
+            Some(column) => column.len(),
+            None => 0
+        };
+
+        let mut rng: StdRng = SeedableRng::from_seed(&[seed][..]);
+        let mut points: Vec<Point> = (0..width * height).map(|_| {
+            (rng.gen_range(0.0, width as f32), rng.gen_range(0.0, height as f32))
+        }).collect();
+
+        let mut polygons = tessellate(&points, width as f32, height as f32);
+        for _ in 0..RELAXATION_STEPS {
+            points = polygons.iter().zip(points.iter()).map(|(polygon, &point)| {
+                match polygon.len() {
+                    0 => point,
+                    _ => centroid(&polygon.iter().map(|&(p, _)| p).collect::<Vec<Point>>())
+                }
+            }).collect();
+            polygons = tessellate(&points, width as f32, height as f32);
+        }
+
+        // Adjacency of all cells, symmetric even when rounding makes a short
+        // edge visible from one side only
+        let mut adjacent: Vec<Vec<usize>> = vec![Vec::new(); points.len()];
+        for (i, polygon) in polygons.iter().enumerate() {
+            for k in 0..polygon.len() {
+                let (a, label) = polygon[k];
+                let (b, _) = polygon[(k + 1) % polygon.len()];
+                if let Some(j) = label {
+                    if distance(a, b) >= MIN_EDGE && !adjacent[i].contains(&j) {
+                        adjacent[i].push(j);
+                        adjacent[j].push(i);
+                    }
+                }
+            }
+        }
+
+        let inside = |p: Point| {
+            let x = (p.0 as usize).min(width - 1);
+            let y = (p.1 as usize).min(height - 1);
+            mask[x][y]
+        };
+
+        // Largest connected group of cells inside the mask
+        let mut component: Vec<Option<usize>> = vec![None; points.len()];
+        let mut sizes: Vec<usize> = Vec::new();
+        for start in 0..points.len() {
+            if component[start].is_some() || !inside(points[start]) {
+                continue;
+            }
+
+            let mut stack = vec![start];
+            component[start] = Some(sizes.len());
+            let mut size = 0;
+            while let Some(i) = stack.pop() {
+                size += 1;
+                for &j in adjacent[i].iter() {
+                    if component[j].is_none() && inside(points[j]) {
+                        component[j] = Some(sizes.len());
+                        stack.push(j);
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+
+        let largest = (0..sizes.len()).max_by_key(|&c| sizes[c]);
+        let mut ids: Vec<Option<usize>> = vec![None; points.len()];
+        let mut kept: Vec<usize> = Vec::new();
+        for i in 0..points.len() {
+            if largest.is_some() && component[i] == largest {
+                ids[i] = Some(kept.len());
+                kept.push(i);
+            }
+        }
+
+        Voronoi {
+            width: width as f32,
+            height: height as f32,
+            sites: kept.iter().map(|&i| points[i]).collect(),
+            polygons: kept.iter().map(|&i| polygons[i].iter().map(|&(p, _)| p).collect()).collect(),
+            across: kept.iter().map(|&i| {
+                polygons[i].iter().map(|&(_, label)| label.and_then(|j| ids[j])).collect()
+            }).collect(),
+            neighbors: kept.iter().map(|&i| adjacent[i].iter().filter_map(|&j| ids[j]).collect()).collect()
+        }
+    }
+
+    /// Cell on the other side of each edge of the polygon, `None` on the
+    /// outline of the maze.
+    pub fn across(&self, id: usize) -> &Vec<Option<usize>> {
+        &self.across[id]
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Corners of the cell, edge `i` runs from corner `i` to corner `i + 1`.
+    pub fn polygon(&self, id: usize) -> &Vec<Point> {
+        &self.polygons[id]
+    }
+
+    /// Point the cell was grown from.
+    pub fn site(&self, id: usize) -> Point {
+        self.sites[id]
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+}
+
+impl Topology for Voronoi {
+    fn size(&self) -> usize {
+        self.sites.len()
+    }
+
+    fn neighbor_ids(&self, id: usize) -> Vec<usize> {
+        self.neighbors[id].clone()
+    }
+
+    /// Cells with an edge on the outline of the maze.
+    fn is_boundary_id(&self, id: usize) -> bool {
+        self.across[id].iter().any(|j| j.is_none())
+    }
+}
+
+impl Maze<Voronoi> {
+    pub fn to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) {
+        self.try_to_png(cell_size, wall_size, color_cell, color_wall, output_filename).unwrap()
+    }
+
+    /// Same as `to_png`, failing when the image can't be written.
+    pub fn try_to_png(&self, cell_size: u32, wall_size: u32, color_cell: &[u8; 3], color_wall: &[u8; 3], output_filename: &'static str) -> Result<(), MazeError> {
+        output::png_voronoi::try_format(self, cell_size, wall_size, color_cell, color_wall, output_filename)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::topology::Topology;
+
+    #[test]
+    fn test_same_seed_same_cells() {
+        assert_eq!(Voronoi::new(6, 4, 7), Voronoi::new(6, 4, 7));
+        assert!(Voronoi::new(6, 4, 7) != Voronoi::new(6, 4, 8));
+    }
+
+    #[test]
+    fn test_neighbors_are_symmetric() {
+        let voronoi = Voronoi::new(8, 6, 1);
+        assert_eq!(voronoi.size(), 48);
+        for id in 0..voronoi.size() {
+            for n in voronoi.neighbor_ids(id) {
+                assert!(voronoi.neighbor_ids(n).contains(&id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_mask_drops_cells() {
+        let mut mask = vec![vec![true; 6]; 6];
+        for x in 0..3 {
+            for y in 0..3 {
+                mask[x][y] = false;
+            }
+        }
+
+        let voronoi = Voronoi::with_mask(&mask, 3);
+        assert!(voronoi.size() < 36);
+        for id in 0..voronoi.size() {
+            let (x, y) = voronoi.site(id);
+            assert!(x >= 3.0 || y >= 3.0);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+extern crate rand;
+
+use std::collections::HashMap;
+
+use rand::{Rng, SeedableRng, StdRng};
+
+use super::cell::BaseCell;
+use super::grid::Grid;
+use super::super::generator;
+
+/// Streams of random numbers drawn for a chunk, kept apart so the
+/// openings of a border don't depend on the maze inside either chunk.
+const STREAM_CHUNK: usize = 0;
+const STREAM_EAST: usize = 1;
+const STREAM_SOUTH: usize = 2;
+
+/// Splits a world coordinate into the chunk and the position inside it.
+fn split(coord: i64, size: usize) -> (i64, usize) {
+    let size = size as i64;
+    let chunk = match coord < 0 {
+        true => (coord + 1) / size - 1,
+        false => coord / size
+    };
+    (chunk, (coord - chunk * size) as usize)
+}
+
+/// Square chunk of an infinite maze, with its passages and the openings
+/// through each of its four borders.
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub cx: i64,
+    pub cy: i64,
+    pub grid: Grid<BaseCell>,
+    /// Column of the opening through the north and south border
+    pub north: usize,
+    pub south: usize,
+    /// Row of the opening through the west and east border
+    pub west: usize,
+    pub east: usize
+}
+
+/// Maze covering the whole plane, split into `size x size` chunks.
+///
+/// Every chunk is a perfect maze generated from the world seed and its
+/// position only, so any chunk can be generated on its own, in any order.
+/// Neighbouring chunks are joined by exactly one opening on their shared
+/// border, which both of them derive from the seed, so the whole maze is
+/// connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct World {
+    seed: usize,
+    size: usize
+}
+
+impl World {
+    pub fn new(seed: usize, size: usize) -> World {
+        World {
+            seed: seed,
+            size: size
+        }
+    }
+
+    fn rng(&self, cx: i64, cy: i64, stream: usize) -> StdRng {
+        SeedableRng::from_seed(&[self.seed, cx as usize, cy as usize, stream][..])
+    }
+
+    pub fn chunk(&self, cx: i64, cy: i64) -> Chunk {
+        let mut grid: Grid<BaseCell> = Grid::new(self.size, self.size);
+        generator::aldous_broder::generate_with_rng(&mut grid, &mut self.rng(cx, cy, STREAM_CHUNK));
+
+        Chunk {
+            cx: cx,
+            cy: cy,
+            grid: grid,
+            north: self.south_opening(cx, cy - 1),
+            south: self.south_opening(cx, cy),
+            west: self.east_opening(cx - 1, cy),
+            east: self.east_opening(cx, cy)
+        }
+    }
+
+    /// Row of the opening between the chunk and the one east of it.
+    pub fn east_opening(&self, cx: i64, cy: i64) -> usize {
+        self.rng(cx, cy, STREAM_EAST).gen_range(0, self.size)
+    }
+
+    pub fn seed(&self) -> usize {
+        self.seed
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Column of the opening between the chunk and the one south of it.
+    pub fn south_opening(&self, cx: i64, cy: i64) -> usize {
+        self.rng(cx, cy, STREAM_SOUTH).gen_range(0, self.size)
+    }
+
+    /// Part of the maze with its top left cell at world coordinates `(x, y)`,
+    /// as a grid which can be rendered by any of the outputs. Passages
+    /// leading out of the window are not shown.
+    pub fn viewport(&self, x: i64, y: i64, width: usize, height: usize) -> Grid<BaseCell> {
+        let mut chunks: HashMap<(i64, i64), Chunk> = HashMap::new();
+        let mut grid: Grid<BaseCell> = Grid::new(width, height);
+
+        for vx in 0..width {
+            for vy in 0..height {
+                let (cx, lx) = split(x + vx as i64, self.size);
+                let (cy, ly) = split(y + vy as i64, self.size);
+
+                if !chunks.contains_key(&(cx, cy)) {
+                    chunks.insert((cx, cy), self.chunk(cx, cy));
+                }
+                let chunk = &chunks[&(cx, cy)];
+
+                if vx + 1 < width {
+                    let open = match lx + 1 < self.size {
+                        true => chunk.grid.is_linked_indices(lx, ly, lx + 1, ly),
+                        false => chunk.east == ly
+                    };
+                    if open {
+                        grid.link_indices(vx, vy, vx + 1, vy);
+                    }
+                }
+
+                if vy + 1 < height {
+                    let open = match ly + 1 < self.size {
+                        true => chunk.grid.is_linked_indices(lx, ly, lx, ly + 1),
+                        false => chunk.south == lx
+                    };
+                    if open {
+                        grid.link_indices(vx, vy, vx, vy + 1);
+                    }
+                }
+            }
+        }
+
+        return grid;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::distance;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(0, 4), (0, 0));
+        assert_eq!(split(5, 4), (1, 1));
+        assert_eq!(split(-1, 4), (-1, 3));
+        assert_eq!(split(-4, 4), (-1, 0));
+        assert_eq!(split(-5, 4), (-2, 3));
+    }
+
+    #[test]
+    fn test_chunks_are_deterministic() {
+        let world = World::new(42, 6);
+        let chunk = world.chunk(-3, 7);
+        let again = world.chunk(-3, 7);
+        assert_eq!(chunk.grid.links, again.grid.links);
+        assert_eq!(chunk.east, world.chunk(-2, 7).west);
+        assert_eq!(chunk.south, world.chunk(-3, 8).north);
+    }
+
+    #[test]
+    fn test_viewport_is_connected() {
+        let world = World::new(7, 5);
+        // Whole chunks only, cutting through one may cut its passages
+        let viewport = world.viewport(-10, -5, 15, 15);
+        let distances = distance::dijkstra::distances(&viewport, 0);
+        for id in 0..viewport.size() {
+            assert!(distances.get(id).is_some());
+        }
+    }
+}
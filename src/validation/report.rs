@@ -0,0 +1,229 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use super::super::distance;
+use super::super::types::cell::Cell;
+use super::super::types::grid::Grid;
+
+type Link = ((usize, usize), (usize, usize));
+
+/// What is wrong with a maze, if anything. Masked cells are not part of the
+/// maze and links to them are left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Groups of cells joined by links in either direction, each sorted.
+    pub components: Vec<Vec<(usize, usize)>>,
+    /// Cells which can't be reached from the entrance, or from the first cell
+    /// when there is none, following one-way passages their way only.
+    pub unreachable: Vec<(usize, usize)>,
+    /// Number of independent cycles, zero when there is a single path
+    /// between any two cells.
+    pub cycles: usize,
+    /// Links without a way back, one-way passages included.
+    pub asymmetric: Vec<Link>,
+    /// Links between cells which aren't neighbors, portals included. Each
+    /// link is given once, lower cell first.
+    pub non_adjacent: Vec<Link>
+}
+
+impl Report {
+    /// Whether the maze is a single tree of two-way passages between
+    /// neighbors, every cell reachable by exactly one path.
+    pub fn is_perfect(&self) -> bool {
+        self.components.len() == 1 && self.unreachable.is_empty() && self.cycles == 0 &&
+            self.asymmetric.is_empty() && self.non_adjacent.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut res = format!("Components: {}\n", self.components.len());
+        if self.components.len() > 1 {
+            for component in self.components.iter() {
+                res.push_str(&format!("  {} cells from {:?}\n", component.len(), component[0]));
+            }
+        }
+
+        res.push_str(&format!("Unreachable cells: {}\n", self.unreachable.len()));
+        for cell in self.unreachable.iter() {
+            res.push_str(&format!("  {:?}\n", cell));
+        }
+
+        res.push_str(&format!("Cycles: {}\n", self.cycles));
+
+        res.push_str(&format!("Asymmetric links: {}\n", self.asymmetric.len()));
+        for &(from, to) in self.asymmetric.iter() {
+            res.push_str(&format!("  {:?} -> {:?}\n", from, to));
+        }
+
+        res.push_str(&format!("Non-adjacent links: {}\n", self.non_adjacent.len()));
+        for &(a, b) in self.non_adjacent.iter() {
+            res.push_str(&format!("  {:?} - {:?}\n", a, b));
+        }
+
+        res.push_str(match self.is_perfect() {
+            true => "Perfect: yes\n",
+            false => "Perfect: no\n"
+        });
+
+        write!(f, "{}", res)
+    }
+}
+
+/// Checks the connectivity, cycles and links of the grid.
+pub fn validate<T>(grid: &Grid<T>) -> Report
+    where T: Cell + Clone
+{
+    let shape = *grid.shape();
+    let inside = |cell: (usize, usize)| !grid.is_masked(cell.0, cell.1);
+
+    let mut asymmetric = Vec::new();
+    let mut non_adjacent = BTreeSet::new();
+    // Links in both directions, once each, lower cell first
+    let mut edges = BTreeSet::new();
+    let mut adjacent: Vec<Vec<usize>> = vec![Vec::new(); grid.size()];
+
+    for (from, to) in grid.links.pairs() {
+        if !inside(from) || !inside(to) {
+            continue;
+        }
+
+        let key = match from < to {
+            true => (from, to),
+            false => (to, from)
+        };
+
+        if !grid.links.contains(to, from) {
+            asymmetric.push((from, to));
+        }
+
+        if !shape.neighbors(from.0, from.1).contains(&to) {
+            non_adjacent.insert(key);
+        }
+
+        if edges.insert(key) {
+            adjacent[shape.id(from.0, from.1)].push(shape.id(to.0, to.1));
+            adjacent[shape.id(to.0, to.1)].push(shape.id(from.0, from.1));
+        }
+    }
+
+    let mut components = Vec::new();
+    let mut visited = vec![false; grid.size()];
+    for id in 0..grid.size() {
+        if visited[id] || !inside(shape.coords(id)) {
+            continue;
+        }
+
+        visited[id] = true;
+        let mut component = Vec::new();
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            component.push(shape.coords(current));
+            for &next in adjacent[current].iter() {
+                if !visited[next] {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        component.sort();
+        components.push(component);
+    }
+
+    let cells: usize = components.iter().map(|component| component.len()).sum();
+
+    let start = match grid.entrance() {
+        Some(entrance) => Some(entrance.cell()),
+        None => components.first().map(|component| component[0])
+    };
+
+    let unreachable = match start {
+        Some(start) => {
+            let distances = distance::dijkstra::distances(grid, shape.id(start.0, start.1));
+            (0..grid.size())
+                .map(|id| shape.coords(id))
+                .filter(|&cell| inside(cell) && distances.get(shape.id(cell.0, cell.1)).is_none())
+                .collect()
+        },
+        None => Vec::new()
+    };
+
+    Report {
+        cycles: edges.len() + components.len() - cells,
+        components: components,
+        unreachable: unreachable,
+        asymmetric: asymmetric,
+        non_adjacent: non_adjacent.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::types::cell::BaseCell;
+
+    #[test]
+    fn test_generated_mazes_are_perfect() {
+        let mut grid: Grid<BaseCell> = Grid::new(6, 5);
+        grid.generate_aldous_broder();
+        grid.place_corners();
+        assert!(grid.is_perfect());
+
+        let mut grid: Grid<BaseCell> = Grid::new(6, 5);
+        grid.generate_sidewinder();
+        assert!(grid.is_perfect());
+
+        let mut grid: Grid<BaseCell> = Grid::new(6, 5);
+        grid.generate_binary();
+        assert!(grid.is_perfect());
+
+        let mut grid: Grid<BaseCell> = Grid::new(6, 5);
+        grid.generate_wilson();
+        assert!(grid.is_perfect());
+    }
+
+    #[test]
+    fn test_validate_finds_problems() {
+        let mut grid: Grid<BaseCell> = Grid::new(3, 2);
+        // A cycle around the four western cells
+        grid.link_indices(0, 0, 1, 0);
+        grid.link_indices(1, 0, 1, 1);
+        grid.link_indices(1, 1, 0, 1);
+        grid.link_indices(0, 1, 0, 0);
+        grid.link_one_way_indices(2, 0, 1, 0);
+        grid.link_indices(0, 0, 2, 1);
+
+        let report = grid.validate();
+        assert_eq!(report.components.len(), 1);
+        assert_eq!(report.cycles, 1);
+        // Nothing leads to (2, 0)
+        assert_eq!(report.unreachable, vec![(2, 0)]);
+        assert_eq!(report.asymmetric, vec![((2, 0), (1, 0))]);
+        assert_eq!(report.non_adjacent, vec![((0, 0), (2, 1))]);
+        assert!(!report.is_perfect());
+
+        let grid: Grid<BaseCell> = Grid::new(2, 1);
+        let report = grid.validate();
+        assert_eq!(report.components, vec![vec![(0, 0)], vec![(1, 0)]]);
+        assert_eq!(report.unreachable, vec![(1, 0)]);
+        assert_eq!(report.cycles, 0);
+    }
+
+    #[test]
+    fn test_validate_lenient_document() {
+        // The link back from (1, 0) to (0, 0) is missing
+        let json = r#"{"x": 3, "y": 1, "links": [[[0, 0], [1, 0]], [[1, 0], [2, 0]], [[2, 0], [1, 0]]]}"#;
+        assert!(Grid::<BaseCell>::from_json(json).is_err());
+
+        let grid: Grid<BaseCell> = Grid::from_json_lenient(json).unwrap();
+        let report = grid.validate();
+        assert_eq!(report.asymmetric, vec![((0, 0), (1, 0))]);
+        assert_eq!(report.components.len(), 1);
+        assert!(report.unreachable.is_empty());
+        assert!(!report.is_perfect());
+
+        let outside = r#"{"x": 2, "y": 1, "links": [[[0, 0], [2, 0]]]}"#;
+        assert!(Grid::<BaseCell>::from_json_lenient(outside).is_err());
+    }
+}
@@ -51,7 +51,10 @@ pub fn start_web<'a>(port: u16) {
 
         let start = PreciseTime::now();
         for _ in 0..count {
-            let mut grid: Grid<BaseCell> = Grid::new(width, height);
+            let mut grid: Grid<BaseCell> = match Grid::try_new(width, height) {
+                Ok(grid) => grid,
+                Err(e) => return Ok(Response::with((status::BadRequest, format!("{}\n", e))))
+            };
             grid.generate_aldous_broder();
 
             res += &grid.to_string()[..];